@@ -0,0 +1,76 @@
+//! Times the day 3 ratings on large generated diagnostic reports.
+//!
+//! Run with `cargo bench --bench day03`.
+
+use aoc_2021::day03::{self, CO2_SCRUBBER, OXYGEN_GENERATOR};
+use aoc_2021::testutil::Random;
+use aoc_2021::MyDuration;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 5;
+
+/// A report of `lines` random lines of `width` bits.
+fn report(lines: usize, width: usize) -> Vec<String> {
+    let mut random = Random(2021);
+    (0..lines)
+        .map(|_| {
+            (0..width)
+                .map(|_| {
+                    if random.next_u64() >> 63 == 1 {
+                        '1'
+                    } else {
+                        '0'
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn mean(mut f: impl FnMut()) -> MyDuration {
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        f();
+        total += start.elapsed();
+    }
+    MyDuration(total / ITERATIONS)
+}
+
+fn main() {
+    println!(
+        "{:>9} {:>6} │ {:>10} │ {:>10} │ {:>10} │ {:>10}",
+        "lines", "bits", "parse", "sort", "filters", "part 2"
+    );
+    for (lines, width) in [
+        (1_000, 12),
+        (100_000, 12),
+        (100_000, 64),
+        (1_000_000, 32),
+        (10_000, 1_000),
+    ] {
+        let report = report(lines, width);
+        let nums = day03::parse_input(&report).unwrap();
+        let mut sorted = nums.clone();
+        sorted.sort_unstable();
+
+        let parse = mean(|| {
+            day03::parse_input(&report).unwrap();
+        });
+        let sort = mean(|| {
+            let mut nums = nums.clone();
+            nums.sort_unstable();
+        });
+        let filters = mean(|| {
+            day03::filter_sorted(&sorted, OXYGEN_GENERATOR).unwrap();
+            day03::filter_sorted(&sorted, CO2_SCRUBBER).unwrap();
+        });
+        let part2 = mean(|| {
+            day03::solve(&report, 2).unwrap();
+        });
+        println!(
+            "{:>9} {:>6} │ {} │ {} │ {} │ {}",
+            lines, width, parse, sort, filters, part2
+        );
+    }
+}
@@ -0,0 +1,43 @@
+//! Times day 4 bingo on large generated sets of boards and draws.
+//!
+//! Run with `cargo bench --bench day04`.
+
+use aoc_2021::day04::{self, Board};
+use aoc_2021::testutil::Random;
+use aoc_2021::MyDuration;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 5;
+
+fn main() {
+    println!(
+        "{:>7} {:>5} {:>7} │ {:>10} │ {:>6}",
+        "boards", "size", "draws", "play", "wins"
+    );
+    let mut random = Random(2021);
+    for (boards, size, draws) in [(100, 5, 100), (10_000, 5, 1_000), (1_000, 50, 10_000)] {
+        let numbers = random.sample(draws * 2, draws);
+        let boards: Vec<Board> = (0..boards)
+            .map(|_| {
+                let cells = random.sample(draws * 2, size * size);
+                Board::new(cells.chunks(size).map(<[usize]>::to_vec).collect()).unwrap()
+            })
+            .collect();
+
+        let mut total = Duration::ZERO;
+        let mut wins = 0;
+        for _ in 0..ITERATIONS {
+            let start = Instant::now();
+            wins = day04::play(&numbers, &boards).len();
+            total += start.elapsed();
+        }
+        println!(
+            "{:>7} {:>5} {:>7} │ {} │ {:>6}",
+            boards.len(),
+            size,
+            draws,
+            MyDuration(total / ITERATIONS),
+            wins
+        );
+    }
+}
@@ -0,0 +1,58 @@
+#include <stdio.h>
+#include <string.h>
+
+#include "aoc.h"
+
+static int failures = 0;
+
+static void check(uint8_t day, uint8_t part, const char *input, const char *expected)
+{
+    char out[64];
+    int64_t len = aoc_solve(day, part, (const uint8_t *)input, strlen(input), out, sizeof(out));
+
+    if (len < 0 || strcmp(out, expected) != 0) {
+        fprintf(stderr, "day %u part %u: expected %s, got %s (%lld)\n", day, part, expected,
+                len < 0 ? "<error>" : out, (long long)len);
+        failures++;
+    }
+}
+
+static void check_error(uint8_t day, uint8_t part, const char *input, int64_t expected)
+{
+    char out[64];
+    int64_t ret = aoc_solve(day, part, (const uint8_t *)input, strlen(input), out, sizeof(out));
+
+    if (ret != expected) {
+        fprintf(stderr, "day %u part %u: expected error %lld, got %lld\n", day, part,
+                (long long)expected, (long long)ret);
+        failures++;
+    }
+}
+
+int main(void)
+{
+    const char *day01 = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+    const char *day02 = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n";
+
+    check(1, 1, day01, "7");
+    check(1, 2, day01, "5");
+    check(2, 1, day02, "150");
+    check(2, 2, day02, "900");
+    check(6, 2, "3,4,3,1,2", "26984457539");
+    check(7, 1, "16,1,2,0,4,2,7,1,2,14", "37");
+    check(16, 2, "9C0141080250320F1802104A08", "1");
+
+    check_error(0, 1, "", AOC_ERR_UNKNOWN_PUZZLE);
+    check_error(1, 3, day01, AOC_ERR_UNKNOWN_PUZZLE);
+    check_error(7, 1, "1,x", AOC_ERR_PARSE_FAILED);
+
+    if (aoc_max_day() < 16) {
+        fprintf(stderr, "aoc_max_day: expected at least 16, got %u\n", aoc_max_day());
+        failures++;
+    }
+
+    if (failures == 0) {
+        printf("All FFI checks passed\n");
+    }
+    return failures != 0;
+}
@@ -0,0 +1,56 @@
+#ifndef AOC_2021_H
+#define AOC_2021_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit by hand. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * The requested day or part does not exist.
+ */
+#define AOC_ERR_UNKNOWN_PUZZLE -1
+
+/**
+ * The input is not valid UTF-8, or a required pointer is null.
+ */
+#define AOC_ERR_INVALID_INPUT -2
+
+/**
+ * The solver failed on the given input.
+ */
+#define AOC_ERR_SOLVER_FAILED -3
+
+/**
+ * The input could not be parsed as that day's puzzle input.
+ */
+#define AOC_ERR_PARSE_FAILED -4
+
+/**
+ * Solves `part` of `day` for the puzzle input in `input_ptr[0..input_len]`.
+ *
+ * The answer is written to `out_buf` as a NUL-terminated string, truncated
+ * to fit `out_len` bytes. Returns the length of the full answer (excluding
+ * the NUL), so a return value `>= out_len` means the answer was truncated.
+ * Returns one of the negative `AOC_ERR_*` codes on failure; for
+ * `AOC_ERR_PARSE_FAILED` the parse error, with its line and column, is
+ * written to `out_buf` in place of the answer.
+ *
+ * # Safety
+ *
+ * `input_ptr` must point to `input_len` readable bytes, and `out_buf` must
+ * point to `out_len` writable bytes (it may be null if `out_len` is 0).
+ */
+int64_t aoc_solve(uint8_t day,
+                  uint8_t part,
+                  const uint8_t *input_ptr,
+                  uintptr_t input_len,
+                  char *out_buf,
+                  uintptr_t out_len);
+
+/**
+ * Returns the highest day number that `aoc_solve` accepts.
+ */
+uint8_t aoc_max_day(void);
+
+#endif  /* AOC_2021_H */
@@ -1,25 +1,222 @@
-fn sequence_increases(sequence: Vec<isize>) -> usize {
-    let pairs = sequence.as_slice().windows(2);
-    let differences = pairs.map(|p| p[1] - p[0]);
-    differences.filter(|&x| x > 0).count()
+use crate::parse::{self, ParseResult};
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+/// Measurements before an outlier that make up its rolling mean.
+pub const OUTLIER_WINDOW: usize = 10;
+/// How many standard deviations from the rolling mean make an outlier.
+pub const OUTLIER_THRESHOLD: f64 = 3.0;
+
+/// Counts how often the sum of a sliding window of measurements is larger
+/// than the sum of the window before it. Neighbouring windows share all but
+/// one measurement, so this compares `x[i]` with `x[i - window]`, keeping
+/// only the last `window` measurements around.
+pub fn count_increases<T: PartialOrd>(
+    measurements: impl IntoIterator<Item = T>,
+    window: usize,
+) -> usize {
+    assert!(window > 0, "window must not be empty");
+    let mut previous = VecDeque::with_capacity(window);
+    let mut increases = 0;
+    for x in measurements {
+        if previous.len() == window {
+            let oldest = previous.pop_front().unwrap();
+            increases += (x > oldest) as usize;
+        }
+        previous.push_back(x);
+    }
+    increases
 }
 
 fn part1(numbers: &[isize]) -> usize {
-    sequence_increases(numbers.to_vec())
+    count_increases(numbers, 1)
 }
 
 fn part2(numbers: &[isize]) -> usize {
-    let windows = numbers.windows(3);
-    let sums = windows.map(|w| w[0] + w[1] + w[2]).collect();
-    sequence_increases(sums)
+    count_increases(numbers, 3)
 }
 
-pub fn run(lines: &[String]) {
-    let numbers: Vec<isize> = lines
-        .iter()
-        .map(|line| line.parse::<isize>().unwrap())
+/// A stretch of measurements, from index `start` to `end` inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Run {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Run {
+    pub fn measurements(&self) -> usize {
+        self.end - self.start + 1
+    }
+}
+
+/// The change in depth from the measurement before `index` to `index`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Jump {
+    pub index: usize,
+    pub change: isize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Outlier {
+    pub index: usize,
+    pub depth: isize,
+    /// The mean of the measurements in the window before this one.
+    pub mean: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DepthProfile {
+    pub measurements: usize,
+    pub longest_increasing: Option<Run>,
+    pub longest_decreasing: Option<Run>,
+    /// Every stretch of at least two equal measurements.
+    pub plateaus: Vec<Run>,
+    pub largest_rise: Option<Jump>,
+    pub largest_drop: Option<Jump>,
+    pub window: usize,
+    pub threshold: f64,
+    pub outliers: Vec<Outlier>,
+}
+
+/// Every maximal stretch of at least two measurements in which each
+/// neighbouring pair satisfies `step`.
+fn runs(depths: &[isize], step: impl Fn(isize, isize) -> bool) -> Vec<Run> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for end in 1..=depths.len() {
+        if end == depths.len() || !step(depths[end - 1], depths[end]) {
+            if end - start >= 2 {
+                runs.push(Run {
+                    start,
+                    end: end - 1,
+                });
+            }
+            start = end;
+        }
+    }
+    runs
+}
+
+/// The first of the longest runs.
+fn longest(runs: Vec<Run>) -> Option<Run> {
+    runs.into_iter().fold(None, |best, run| match best {
+        Some(b) if b.measurements() >= run.measurements() => Some(b),
+        _ => Some(run),
+    })
+}
+
+/// Measurements further than `threshold` standard deviations from the mean
+/// of the `window` measurements before them.
+fn outliers(depths: &[isize], window: usize, threshold: f64) -> Vec<Outlier> {
+    let mut outliers = Vec::new();
+    let (mut sum, mut squares) = (0.0, 0.0);
+    for (index, &depth) in depths.iter().enumerate() {
+        if index >= window {
+            let mean = sum / window as f64;
+            let deviation = (squares / window as f64 - mean * mean).max(0.0).sqrt();
+            if (depth as f64 - mean).abs() > threshold * deviation {
+                outliers.push(Outlier { index, depth, mean });
+            }
+            let oldest = depths[index - window] as f64;
+            sum -= oldest;
+            squares -= oldest * oldest;
+        }
+        sum += depth as f64;
+        squares += depth as f64 * depth as f64;
+    }
+    outliers
+}
+
+pub fn depth_profile(depths: &[isize], window: usize, threshold: f64) -> DepthProfile {
+    assert!(window > 0, "window must not be empty");
+    let jumps: Vec<Jump> = depths
+        .windows(2)
+        .enumerate()
+        .map(|(i, pair)| Jump {
+            index: i + 1,
+            change: pair[1] - pair[0],
+        })
         .collect();
 
+    DepthProfile {
+        measurements: depths.len(),
+        longest_increasing: longest(runs(depths, |a, b| b > a)),
+        longest_decreasing: longest(runs(depths, |a, b| b < a)),
+        plateaus: runs(depths, |a, b| a == b),
+        largest_rise: jumps
+            .iter()
+            .filter(|j| j.change > 0)
+            .fold(None, |best: Option<Jump>, &j| match best {
+                Some(b) if b.change >= j.change => Some(b),
+                _ => Some(j),
+            }),
+        largest_drop: jumps
+            .iter()
+            .filter(|j| j.change < 0)
+            .fold(None, |best: Option<Jump>, &j| match best {
+                Some(b) if b.change <= j.change => Some(b),
+                _ => Some(j),
+            }),
+        window,
+        threshold,
+        outliers: outliers(depths, window, threshold),
+    }
+}
+
+impl Display for DepthProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let run = |run: Option<Run>| match run {
+            Some(r) => format!(
+                "{} measurements, #{} to #{}",
+                r.measurements(),
+                r.start,
+                r.end
+            ),
+            None => "none".to_string(),
+        };
+        let jump = |jump: Option<Jump>| match jump {
+            Some(j) => format!("{:+} at #{}", j.change, j.index),
+            None => "none".to_string(),
+        };
+
+        writeln!(f, "Measurements:        {}", self.measurements)?;
+        writeln!(f, "Longest increasing:  {}", run(self.longest_increasing))?;
+        writeln!(f, "Longest decreasing:  {}", run(self.longest_decreasing))?;
+        writeln!(
+            f,
+            "Plateaus:            {} (longest: {})",
+            self.plateaus.len(),
+            run(longest(self.plateaus.clone()))
+        )?;
+        writeln!(f, "Largest rise:        {}", jump(self.largest_rise))?;
+        writeln!(f, "Largest drop:        {}", jump(self.largest_drop))?;
+        write!(
+            f,
+            "Outliers:            {} (over {}σ from the mean of the previous {})",
+            self.outliers.len(),
+            self.threshold,
+            self.window
+        )?;
+        for o in &self.outliers {
+            write!(f, "\n  #{}: {} (mean {:.1})", o.index, o.depth, o.mean)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn parse_input(lines: &[String]) -> ParseResult<Vec<isize>> {
+    parse::lines(lines, parse::number)
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let numbers = parse_input(lines)?;
+
+    solve_parts!(numbers, part)
+}
+
+pub fn run(lines: &[String]) {
+    let numbers = parse_input(lines).unwrap();
+
     run_parts!(numbers);
 }
 
@@ -37,4 +234,62 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(&NUMBERS), 5);
     }
+
+    #[test]
+    fn test_count_increases() {
+        for window in 1..=NUMBERS.len() {
+            let sums: Vec<isize> = NUMBERS.windows(window).map(|w| w.iter().sum()).collect();
+            let expected = sums.windows(2).filter(|p| p[1] > p[0]).count();
+            assert_eq!(count_increases(NUMBERS, window), expected);
+        }
+        assert_eq!(count_increases(NUMBERS, 11), 0);
+
+        let sawtooth = (0..1_000_000).map(|i| i % 7);
+        assert_eq!(count_increases(sawtooth, 7), 0);
+        let lines = "1\n3\n2\n4\n".lines().map(|l| l.parse::<u32>().unwrap());
+        assert_eq!(count_increases(lines, 2), 2);
+    }
+
+    #[test]
+    fn test_depth_profile() {
+        let profile = depth_profile(&NUMBERS, 3, 2.0);
+        assert_eq!(profile.measurements, 10);
+        assert_eq!(profile.longest_increasing, Some(Run { start: 0, end: 3 }));
+        assert_eq!(profile.longest_decreasing, Some(Run { start: 3, end: 4 }));
+        assert!(profile.plateaus.is_empty());
+        assert_eq!(
+            profile.largest_rise,
+            Some(Jump {
+                index: 6,
+                change: 33
+            })
+        );
+        assert_eq!(
+            profile.largest_drop,
+            Some(Jump {
+                index: 4,
+                change: -10
+            })
+        );
+        let outliers: Vec<usize> = profile.outliers.iter().map(|o| o.index).collect();
+        assert_eq!(outliers, [6, 7]);
+        assert!((profile.outliers[0].mean - 617.0 / 3.0).abs() < 1e-9);
+
+        let flat = [5, 5, 5, 6, 6, 4, 4, 4, 4];
+        let profile = depth_profile(&flat, 3, 2.0);
+        assert_eq!(
+            profile.plateaus,
+            [
+                Run { start: 0, end: 2 },
+                Run { start: 3, end: 4 },
+                Run { start: 5, end: 8 }
+            ]
+        );
+        assert_eq!(profile.longest_increasing, Some(Run { start: 2, end: 3 }));
+        let outliers: Vec<usize> = profile.outliers.iter().map(|o| o.index).collect();
+        assert_eq!(outliers, [3, 5]);
+
+        let report = depth_profile(&[], 3, 2.0).to_string();
+        assert!(report.contains("Longest increasing:  none"));
+    }
 }
@@ -1,89 +1,394 @@
+use crate::parse::{self, ParseError, ParseResult};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::io::{self, Write};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum Direction {
     Forward,
+    Back,
     Down,
     Up,
 }
 
-struct Command {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Command {
     dir: Direction,
     amount: usize,
 }
 
-struct Submarine {
-    position: usize,
-    depth: usize,
-    aim: usize,
+/// What happens when a command would take the submarine above the surface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SurfacePolicy {
+    /// Stop at the surface, at depth 0.
+    Clamp,
+    /// Refuse the command.
+    Error,
+    /// Carry on with a negative depth.
+    AllowNegative,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubmarineError {
+    /// The command would have taken the submarine to this negative depth.
+    AboveSurface {
+        depth: isize,
+    },
+    Overflow,
+}
+
+impl Display for SubmarineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmarineError::AboveSurface { depth } => {
+                write!(f, "submarine would surface to depth {}", depth)
+            }
+            SubmarineError::Overflow => write!(f, "submarine left the map"),
+        }
+    }
+}
+
+pub struct Submarine {
+    position: isize,
+    depth: isize,
+    /// Negative when pointing up.
+    aim: isize,
+    policy: SurfacePolicy,
+    trajectory: Option<Trajectory>,
 }
 
 impl Submarine {
-    pub fn new() -> Self {
+    pub fn new(policy: SurfacePolicy) -> Self {
         Self {
             position: 0,
             depth: 0,
             aim: 0,
+            policy,
+            trajectory: None,
         }
     }
 
-    pub fn result(&self) -> usize {
+    /// A submarine that records its state after every command.
+    pub fn recording(policy: SurfacePolicy) -> Self {
+        let mut sub = Self::new(policy);
+        sub.trajectory = Some(Trajectory::default());
+        sub.record();
+        sub
+    }
+
+    pub fn result(&self) -> isize {
         self.position * self.depth
     }
 
-    pub fn command(&mut self, cmd: Command) {
+    /// Applies the surface policy to a new depth.
+    fn checked_depth(&self, depth: Option<isize>) -> Result<isize, SubmarineError> {
+        match depth.ok_or(SubmarineError::Overflow)? {
+            depth if depth >= 0 => Ok(depth),
+            depth => match self.policy {
+                SurfacePolicy::Clamp => Ok(0),
+                SurfacePolicy::Error => Err(SubmarineError::AboveSurface { depth }),
+                SurfacePolicy::AllowNegative => Ok(depth),
+            },
+        }
+    }
+
+    /// Moves as in part 1, where `up` and `down` change the depth directly.
+    /// Nothing changes if the command fails.
+    pub fn command(&mut self, cmd: Command) -> Result<(), SubmarineError> {
+        let amount = isize::try_from(cmd.amount).map_err(|_| SubmarineError::Overflow)?;
         match cmd.dir {
-            Direction::Forward => self.position += cmd.amount,
-            Direction::Down => self.depth += cmd.amount,
-            Direction::Up => self.depth -= cmd.amount,
+            Direction::Forward | Direction::Back => {
+                let amount = if cmd.dir == Direction::Back {
+                    -amount
+                } else {
+                    amount
+                };
+                self.position = self
+                    .position
+                    .checked_add(amount)
+                    .ok_or(SubmarineError::Overflow)?
+            }
+            Direction::Down => self.depth = self.checked_depth(self.depth.checked_add(amount))?,
+            Direction::Up => self.depth = self.checked_depth(self.depth.checked_sub(amount))?,
         }
+        self.record();
+        Ok(())
     }
 
-    pub fn aim(&mut self, cmd: Command) {
+    /// Moves as in part 2, where `up` and `down` change the aim and
+    /// `forward` and `back` move along it. Nothing changes if the command fails.
+    pub fn aim(&mut self, cmd: Command) -> Result<(), SubmarineError> {
+        let amount = isize::try_from(cmd.amount).map_err(|_| SubmarineError::Overflow)?;
         match cmd.dir {
-            Direction::Forward => {
-                self.position += cmd.amount;
-                self.depth += self.aim * cmd.amount;
+            Direction::Forward | Direction::Back => {
+                let amount = if cmd.dir == Direction::Back {
+                    -amount
+                } else {
+                    amount
+                };
+                let position = self.position.checked_add(amount);
+                let dive = self.aim.checked_mul(amount);
+                let depth = self.checked_depth(dive.and_then(|d| self.depth.checked_add(d)))?;
+                self.position = position.ok_or(SubmarineError::Overflow)?;
+                self.depth = depth;
+            }
+            Direction::Down => {
+                self.aim = self
+                    .aim
+                    .checked_add(amount)
+                    .ok_or(SubmarineError::Overflow)?
+            }
+            Direction::Up => {
+                self.aim = self
+                    .aim
+                    .checked_sub(amount)
+                    .ok_or(SubmarineError::Overflow)?
             }
-            Direction::Down => self.aim += cmd.amount,
-            Direction::Up => self.aim -= cmd.amount,
+        }
+        self.record();
+        Ok(())
+    }
+
+    fn record(&mut self) {
+        let state = self.state();
+        if let Some(trajectory) = &mut self.trajectory {
+            trajectory.states.push(state);
         }
     }
+
+    pub fn state(&self) -> SubmarineState {
+        SubmarineState {
+            position: self.position,
+            depth: self.depth,
+            aim: self.aim,
+        }
+    }
+
+    /// Every state so far, if the submarine was created with
+    /// [`Submarine::recording`].
+    pub fn trajectory(&self) -> Option<&Trajectory> {
+        self.trajectory.as_ref()
+    }
 }
 
-fn parse_command(cmd: &str) -> Command {
-    let mut iter = cmd.split_whitespace();
-    let dir: Direction;
-    let amount: usize;
-    match iter.next().unwrap() {
-        "forward" => dir = Direction::Forward,
-        "down" => dir = Direction::Down,
-        "up" => dir = Direction::Up,
-        _ => {
-            panic!("Something went wrong")
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubmarineState {
+    pub position: isize,
+    pub depth: isize,
+    pub aim: isize,
+}
+
+/// The states of a submarine: where it started, and where it was after
+/// each successful command.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Trajectory {
+    states: Vec<SubmarineState>,
+}
+
+impl Trajectory {
+    pub fn states(&self) -> &[SubmarineState] {
+        &self.states
+    }
+
+    pub fn max_depth(&self) -> Option<isize> {
+        self.states.iter().map(|s| s.depth).max()
+    }
+
+    /// The first step after which the submarine was deeper than `depth`,
+    /// where step 0 is the starting state.
+    pub fn first_deeper_than(&self, depth: isize) -> Option<usize> {
+        self.states.iter().position(|s| s.depth > depth)
+    }
+
+    /// Writes `step,position,depth,aim` rows, with a header.
+    pub fn write_csv(&self, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "step,position,depth,aim")?;
+        for (step, s) in self.states.iter().enumerate() {
+            writeln!(out, "{},{},{},{}", step, s.position, s.depth, s.aim)?;
         }
-    };
-    amount = iter.next().unwrap().parse().unwrap();
-    Command { dir, amount }
+        Ok(())
+    }
+}
+
+pub fn parse_command(cmd: &str) -> ParseResult<Command> {
+    let (dir, amount) = parse::pair(
+        cmd,
+        " ",
+        |dir| match dir {
+            "forward" => Ok(Direction::Forward),
+            "back" => Ok(Direction::Back),
+            "down" => Ok(Direction::Down),
+            "up" => Ok(Direction::Up),
+            _ => Err(ParseError::new(0, format!("unknown direction {:?}", dir))),
+        },
+        parse::number,
+    )?;
+    Ok(Command { dir, amount })
+}
+
+/// A parsed line of a submarine script, with macros already expanded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Statement {
+    Command { cmd: Command, line: usize },
+    Repeat { count: usize, body: Vec<Statement> },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub error: SubmarineError,
 }
 
-fn part1(lines: &[String]) -> usize {
-    let mut sub = Submarine::new();
-    let cmds: Vec<Command> = lines.iter().map(|line| parse_command(line)).collect();
-    for cmd in cmds {
-        sub.command(cmd);
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line + 1, self.error)
     }
-    sub.result()
 }
 
-fn part2(lines: &[String]) -> usize {
-    let mut sub = Submarine::new();
-    let cmds: Vec<Command> = lines.iter().map(|line| parse_command(line)).collect();
-    for cmd in cmds {
-        sub.aim(cmd);
+impl From<ScriptError> for ParseError {
+    fn from(e: ScriptError) -> Self {
+        ParseError::new(0, e.error.to_string()).on_line(e.line)
     }
-    sub.result()
+}
+
+/// The words that start a command, which macros cannot be named after.
+const COMMANDS: [&str; 4] = ["forward", "back", "down", "up"];
+
+/// Parses the submarine scripting language. Besides the puzzle's commands
+/// it has `back N`, `# comments`, `repeat N { ... }` blocks, and
+/// `macro NAME { ... }` definitions, invoked by writing `NAME` on a line.
+struct ScriptParser<'a> {
+    lines: &'a [String],
+    next: usize,
+    macros: HashMap<&'a str, Vec<Statement>>,
+}
+
+impl<'a> ScriptParser<'a> {
+    /// Parses statements up to the end of the script, or up to the `}`
+    /// closing the block opened on line `opened`.
+    fn block(&mut self, opened: Option<usize>) -> ParseResult<Vec<Statement>> {
+        let mut statements = Vec::new();
+        while let Some(raw) = self.lines.get(self.next) {
+            let line = self.next;
+            self.next += 1;
+
+            let code = raw.split('#').next().unwrap().trim_end();
+            let indent = code.len() - code.trim_start().len();
+            let code = code.trim_start();
+            let fail = |column: usize, message: String| {
+                Err(ParseError::new(indent + column, message).on_line(line))
+            };
+
+            if code.is_empty() {
+                continue;
+            }
+            if code == "}" {
+                return match opened {
+                    Some(_) => Ok(statements),
+                    None => fail(0, "unmatched '}'".to_string()),
+                };
+            }
+
+            let words: Vec<&str> = code.split_whitespace().collect();
+            match words.as_slice() {
+                ["repeat", count, "{"] => {
+                    let count =
+                        parse::number(count).map_err(|e| e.shifted(indent + 7).on_line(line))?;
+                    let body = self.block(Some(line))?;
+                    statements.push(Statement::Repeat { count, body });
+                }
+                ["macro", name, "{"] => {
+                    if opened.is_some() {
+                        return fail(0, "macros must be defined at the top level".to_string());
+                    }
+                    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                        || COMMANDS.contains(name)
+                        || ["repeat", "macro"].contains(name)
+                    {
+                        return fail(6, format!("invalid macro name {:?}", name));
+                    }
+                    if self.macros.contains_key(name) {
+                        return fail(6, format!("macro {:?} is already defined", name));
+                    }
+                    let body = self.block(Some(line))?;
+                    self.macros.insert(name, body);
+                }
+                ["repeat" | "macro", ..] => {
+                    return fail(0, format!("expected \"{} ... {{\"", words[0]));
+                }
+                [name] if COMMANDS.contains(name) => {
+                    return fail(name.len(), format!("expected an amount after {:?}", name));
+                }
+                [name] => match self.macros.get(name) {
+                    Some(body) => statements.extend(body.iter().cloned()),
+                    None => return fail(0, format!("unknown macro {:?}", name)),
+                },
+                _ => {
+                    let cmd = parse_command(code).map_err(|e| e.shifted(indent).on_line(line))?;
+                    statements.push(Statement::Command { cmd, line });
+                }
+            }
+        }
+
+        match opened {
+            Some(line) => Err(ParseError::new(0, "unclosed '{'").on_line(line)),
+            None => Ok(statements),
+        }
+    }
+}
+
+pub fn parse_script(lines: &[String]) -> ParseResult<Vec<Statement>> {
+    ScriptParser {
+        lines,
+        next: 0,
+        macros: HashMap::new(),
+    }
+    .block(None)
+}
+
+/// Runs a script with one interpretation of the commands, such as
+/// [`Submarine::command`] or [`Submarine::aim`].
+pub fn execute(
+    statements: &[Statement],
+    sub: &mut Submarine,
+    step: fn(&mut Submarine, Command) -> Result<(), SubmarineError>,
+) -> Result<(), ScriptError> {
+    for statement in statements {
+        match statement {
+            Statement::Command { cmd, line } => {
+                step(sub, *cmd).map_err(|error| ScriptError { line: *line, error })?
+            }
+            Statement::Repeat { count, body } => {
+                for _ in 0..*count {
+                    execute(body, sub, step)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn part1(script: &[Statement]) -> ParseResult<isize> {
+    let mut sub = Submarine::new(SurfacePolicy::Error);
+    execute(script, &mut sub, Submarine::command)?;
+    Ok(sub.result())
+}
+
+fn part2(script: &[Statement]) -> ParseResult<isize> {
+    let mut sub = Submarine::new(SurfacePolicy::Error);
+    execute(script, &mut sub, Submarine::aim)?;
+    Ok(sub.result())
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let script = parse_script(lines)?;
+    solve_parts!(script, part; try)
 }
 
 pub fn run(lines: &[String]) {
-    run_parts!(lines);
+    let script = parse_script(lines).unwrap();
+    run_parts!(script; try);
 }
 
 #[cfg(test)]
@@ -103,11 +408,165 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&get_test_input()), 150);
+        assert_eq!(part1(&parse_script(&get_test_input()).unwrap()), Ok(150));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&get_test_input()), 900);
+        assert_eq!(part2(&parse_script(&get_test_input()).unwrap()), Ok(900));
+    }
+
+    fn cmd(s: &str) -> Command {
+        parse_command(s).unwrap()
+    }
+
+    #[test]
+    fn test_surface_policy() {
+        let surface = |policy| {
+            let mut sub = Submarine::new(policy);
+            sub.command(cmd("down 2")).unwrap();
+            let result = sub.command(cmd("up 5"));
+            (result, sub.depth)
+        };
+        assert_eq!(surface(SurfacePolicy::Clamp), (Ok(()), 0));
+        assert_eq!(
+            surface(SurfacePolicy::Error),
+            (Err(SubmarineError::AboveSurface { depth: -3 }), 2)
+        );
+        assert_eq!(surface(SurfacePolicy::AllowNegative), (Ok(()), -3));
+
+        let mut sub = Submarine::new(SurfacePolicy::Error);
+        sub.aim(cmd("up 3")).unwrap();
+        assert_eq!(sub.aim, -3);
+        assert_eq!(
+            sub.aim(cmd("forward 2")),
+            Err(SubmarineError::AboveSurface { depth: -6 })
+        );
+        assert_eq!((sub.position, sub.depth), (0, 0));
+
+        let mut sub = Submarine::new(SurfacePolicy::Clamp);
+        sub.aim(cmd("up 3")).unwrap();
+        sub.aim(cmd("forward 2")).unwrap();
+        assert_eq!((sub.position, sub.depth), (2, 0));
+    }
+
+    fn script(s: &str) -> Vec<String> {
+        s.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_script() {
+        let lines = script(
+            "# The example, using every feature
+macro descend {
+    down 4
+    down 1  # five in total
+}
+forward 5
+descend
+forward 10
+back 2
+repeat 2 {
+    up 1
+    repeat 2 {
+        up 1
+        down 1
+    }
+}
+up 1
+down 8
+forward 2",
+        );
+        let statements = parse_script(&lines).unwrap();
+        assert_eq!(part1(&statements), Ok(150));
+        assert_eq!(part2(&statements), Ok(900));
+        assert_eq!(statements.len(), 9);
+        assert!(matches!(statements[2], Statement::Command { line: 3, .. }));
+        assert!(matches!(&statements[5], Statement::Repeat { count: 2, body } if body.len() == 2));
+    }
+
+    #[test]
+    fn test_script_errors() {
+        let err = |s: &str| {
+            let e = parse_script(&script(s)).unwrap_err();
+            (e.line, e.column)
+        };
+        assert_eq!(err("forward 5\n  sideways 2"), (1, 2));
+        assert_eq!(err("forward 5\nrepeat x {"), (1, 7));
+        assert_eq!(err("repeat 2 {\n  forward 5\n"), (0, 0));
+        assert_eq!(err("forward 1\n}"), (1, 0));
+        assert_eq!(err("dive"), (0, 0));
+        assert_eq!(err("down 1\n  forward"), (1, 9));
+        let e = parse_script(&script("forward")).unwrap_err();
+        assert_eq!(e.message, "expected an amount after \"forward\"");
+        assert_eq!(err("macro up {\n}"), (0, 6));
+        assert_eq!(err("repeat 2 {\n  macro m {\n  }\n}"), (1, 2));
+        assert_eq!(err("macro m {\n  m\n}"), (1, 2));
+
+        let mut sub = Submarine::new(SurfacePolicy::Error);
+        let statements = parse_script(&script("down 1\nrepeat 3 {\n  up 1\n}")).unwrap();
+        let err = execute(&statements, &mut sub, Submarine::command).unwrap_err();
+        assert_eq!(
+            err,
+            ScriptError {
+                line: 2,
+                error: SubmarineError::AboveSurface { depth: -1 }
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "line 3: submarine would surface to depth -1"
+        );
+
+        let lines = script("down 1\nup 2");
+        let err = solve(&lines, 1).unwrap_err();
+        assert_eq!((err.line, err.column), (1, 0));
+        assert_eq!(err.message, "submarine would surface to depth -1");
+        assert_eq!(solve(&lines, 2), Ok(Some("0".to_string())));
+    }
+
+    #[test]
+    fn test_trajectory() {
+        let statements = parse_script(&get_test_input()).unwrap();
+        let mut sub = Submarine::recording(SurfacePolicy::Error);
+        execute(&statements, &mut sub, Submarine::aim).unwrap();
+
+        let trajectory = sub.trajectory().unwrap();
+        assert_eq!(trajectory.states().len(), 7);
+        assert_eq!(trajectory.states()[6], sub.state());
+        assert_eq!(trajectory.max_depth(), Some(60));
+        assert_eq!(trajectory.first_deeper_than(0), Some(3));
+        assert_eq!(trajectory.first_deeper_than(40), Some(6));
+        assert_eq!(trajectory.first_deeper_than(60), None);
+
+        let mut csv = Vec::new();
+        trajectory.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], "step,position,depth,aim");
+        assert_eq!(rows[1], "0,0,0,0");
+        assert_eq!(rows[3], "2,5,0,5");
+        assert_eq!(rows[7], "6,15,60,10");
+
+        let mut sub = Submarine::new(SurfacePolicy::Error);
+        sub.command(cmd("down 3")).unwrap();
+        assert_eq!(sub.trajectory(), None);
+
+        // Failed commands are not recorded
+        let mut sub = Submarine::recording(SurfacePolicy::Error);
+        assert!(sub.command(cmd("up 3")).is_err());
+        assert_eq!(sub.trajectory().unwrap().states().len(), 1);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut sub = Submarine::new(SurfacePolicy::AllowNegative);
+        let huge = format!("down {}", usize::MAX);
+        assert_eq!(sub.command(cmd(&huge)), Err(SubmarineError::Overflow));
+
+        let mut sub = Submarine::new(SurfacePolicy::AllowNegative);
+        sub.aim(cmd(&format!("down {}", isize::MAX))).unwrap();
+        assert_eq!(sub.aim(cmd("forward 2")), Err(SubmarineError::Overflow));
+        assert_eq!(sub.position, 0);
     }
 }
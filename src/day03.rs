@@ -1,83 +1,267 @@
-fn strings_to_nums(lines: &[String]) -> Vec<u32> {
-    lines
-        .iter()
-        .map(|line| u32::from_str_radix(line, 2).unwrap())
-        .collect()
+use crate::parse::{self, ParseError, ParseResult};
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// A report line of any width, as a little-endian bitset: bit 0 is the
+/// rightmost character.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bits {
+    words: Vec<u64>,
+    width: usize,
 }
 
-fn most_common_bits(nums: &[u32], num_bits: usize) -> Vec<u32> {
-    let mut bit_sums = vec![0; num_bits];
+impl Bits {
+    fn zeros(width: usize) -> Self {
+        Self {
+            words: vec![0; width.div_ceil(64)],
+            width,
+        }
+    }
+
+    fn get(&self, bit: usize) -> bool {
+        self.words[bit / 64] >> (bit % 64) & 1 == 1
+    }
 
-    nums.iter().fold(&mut bit_sums, |acc, x| {
-        for (i, item) in acc.iter_mut().enumerate().take(num_bits) {
-            *item += (x >> (num_bits - 1 - i)) & 1;
+    fn set(&mut self, bit: usize) {
+        self.words[bit / 64] |= 1 << (bit % 64);
+    }
+
+    /// Flips every bit within the width.
+    fn complement(&self) -> Bits {
+        let mut flipped = Bits::zeros(self.width);
+        for bit in (0..self.width).filter(|&bit| !self.get(bit)) {
+            flipped.set(bit);
         }
-        acc
-    });
+        flipped
+    }
 
-    for sum in &mut bit_sums {
-        *sum = if *sum as usize >= (nums.len() + 1) / 2 {
-            // Ceiling rounding
-            1
-        } else {
-            0
-        };
+    /// The product of the two numbers, as little-endian words.
+    fn product(&self, other: &Bits) -> Vec<u64> {
+        let mut product = vec![0u64; self.words.len() + other.words.len()];
+        for (i, &a) in self.words.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, &b) in other.words.iter().enumerate() {
+                let sum = product[i + j] as u128 + a as u128 * b as u128 + carry;
+                product[i + j] = sum as u64;
+                carry = sum >> 64;
+            }
+            product[i + other.words.len()] = carry as u64;
+        }
+        product
+    }
+}
+
+/// Orders by value, for bitsets of the same width.
+impl Ord for Bits {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.width
+            .cmp(&other.width)
+            .then_with(|| self.words.iter().rev().cmp(other.words.iter().rev()))
+    }
+}
+
+impl PartialOrd for Bits {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for Bits {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bits = Bits::zeros(s.len());
+        for (column, c) in s.chars().enumerate() {
+            match c {
+                '1' => bits.set(s.len() - 1 - column),
+                '0' => {}
+                _ => {
+                    return Err(ParseError::new(
+                        column,
+                        format!("expected bit, found {:?}", c),
+                    ))
+                }
+            }
+        }
+        Ok(bits)
+    }
+}
+
+/// Formats a little-endian number of any size in decimal.
+fn decimal(words: &[u64]) -> String {
+    const CHUNK: u64 = 10_000_000_000_000_000_000;
+
+    let mut words = words.to_vec();
+    let mut chunks = Vec::new();
+    while words.iter().any(|&w| w != 0) {
+        let mut remainder = 0u128;
+        for word in words.iter_mut().rev() {
+            let current = remainder << 64 | *word as u128;
+            *word = (current / CHUNK as u128) as u64;
+            remainder = current % CHUNK as u128;
+        }
+        chunks.push(remainder as u64);
+    }
+
+    match chunks.split_last() {
+        None => "0".to_string(),
+        Some((most, rest)) => rest.iter().rev().fold(most.to_string(), |acc, chunk| {
+            format!("{}{:019}", acc, chunk)
+        }),
+    }
+}
+
+fn most_common_bits(nums: &[Bits], num_bits: usize) -> Bits {
+    let mut bit_sums = vec![0; num_bits];
+    for num in nums {
+        for (bit, sum) in bit_sums.iter_mut().enumerate() {
+            *sum += num.get(bit) as usize;
+        }
+    }
+
+    let mut common = Bits::zeros(num_bits);
+    for (bit, &sum) in bit_sums.iter().enumerate() {
+        // Ceiling rounding
+        if sum >= nums.len().div_ceil(2) {
+            common.set(bit);
+        }
     }
+    common
+}
+
+/// Which bit value a filter keeps at each position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Criterion {
+    MostCommon,
+    LeastCommon,
+}
+
+/// Which bit value a filter keeps when both are equally common.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TieBreak {
+    KeepOnes,
+    KeepZeros,
+}
+
+/// What a filter does when it runs out of bits with several candidates
+/// left, which happens when the report contains duplicate lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exhausted {
+    Error,
+    AllSurvivors,
+}
 
-    bit_sums
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub criterion: Criterion,
+    pub tie_break: TieBreak,
+    pub exhausted: Exhausted,
 }
 
-fn bit_vec_to_num(bits: Vec<u32>) -> u32 {
-    bits.iter()
-        .rev()
-        .enumerate()
-        .rev()
-        .fold(0, |acc, (p, bit)| acc + *bit as u32 * 2u32.pow(p as u32))
+pub const OXYGEN_GENERATOR: Rule = Rule {
+    criterion: Criterion::MostCommon,
+    tie_break: TieBreak::KeepOnes,
+    exhausted: Exhausted::AllSurvivors,
+};
+
+pub const CO2_SCRUBBER: Rule = Rule {
+    criterion: Criterion::LeastCommon,
+    tie_break: TieBreak::KeepZeros,
+    exhausted: Exhausted::AllSurvivors,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FilterError {
+    NoCandidates,
+    /// Bits ran out with this many identical candidates left.
+    Ambiguous(usize),
 }
 
-fn iterative_filter(nums: &mut Vec<u32>, num_bits: usize, keep_equal: bool) -> u32 {
-    let mut bit = num_bits - 1;
-    let mut common = bit_vec_to_num(most_common_bits(nums, num_bits));
+/// Narrows sorted candidates down bit by bit from the left, keeping the
+/// ones whose bit matches the rule, until one is left. Bits on which every
+/// remaining candidate agrees are skipped, so the filter never empties.
+///
+/// The candidates left always share every bit above the current one, so
+/// those with a 0 in it sort first, and each step is a binary search that
+/// narrows the range of candidates.
+pub fn filter_sorted(sorted: &[Bits], rule: Rule) -> Result<&[Bits], FilterError> {
+    let mut candidates = sorted;
+    let num_bits = candidates.first().ok_or(FilterError::NoCandidates)?.width;
 
-    loop {
-        nums.retain(|x| ((x >> bit) & 1 == (common >> bit) & 1) == keep_equal);
-        if nums.len() == 1 {
+    for bit in (0..num_bits).rev() {
+        if candidates.len() == 1 {
             break;
         }
-        common = bit_vec_to_num(most_common_bits(nums, num_bits));
-        bit -= 1;
+        let zeros = candidates.partition_point(|n| !n.get(bit));
+        let ones = candidates.len() - zeros;
+        if ones == 0 || zeros == 0 {
+            continue;
+        }
+
+        let keep = match (ones.cmp(&zeros), rule.criterion) {
+            (Ordering::Equal, _) => rule.tie_break == TieBreak::KeepOnes,
+            (order, Criterion::MostCommon) => order == Ordering::Greater,
+            (order, Criterion::LeastCommon) => order == Ordering::Less,
+        };
+        candidates = if keep {
+            &candidates[zeros..]
+        } else {
+            &candidates[..zeros]
+        };
+    }
+
+    match (candidates.len(), rule.exhausted) {
+        (1, _) | (_, Exhausted::AllSurvivors) => Ok(candidates),
+        (n, Exhausted::Error) => Err(FilterError::Ambiguous(n)),
     }
+}
+
+/// Sorts the candidates and filters them with [`filter_sorted`].
+pub fn filter(nums: &[Bits], rule: Rule) -> Result<Vec<Bits>, FilterError> {
+    let mut sorted = nums.to_vec();
+    sorted.sort_unstable();
+    filter_sorted(&sorted, rule).map(<[Bits]>::to_vec)
+}
 
-    nums[0]
+fn part1(nums: &[Bits]) -> String {
+    let num_bits = nums[0].width;
+    let gamma = most_common_bits(nums, num_bits);
+    let epsilon = gamma.complement();
+    decimal(&gamma.product(&epsilon))
 }
 
-fn part1(lines: &[String]) -> u32 {
-    let num_bits = lines[0].len();
-    let mask = 2u32.pow(num_bits as u32) - 1;
-    let gamma = bit_vec_to_num(most_common_bits(
-        &strings_to_nums(&lines.to_vec()),
-        num_bits,
-    ));
-    let epsilon = !gamma & mask;
-    gamma * epsilon
+fn part2(nums: &[Bits]) -> String {
+    let mut sorted = nums.to_vec();
+    sorted.sort_unstable();
+    // Any survivors are duplicates of each other
+    let oxygen_generator_rating = &filter_sorted(&sorted, OXYGEN_GENERATOR).unwrap()[0];
+    let co2_scrubber_rating = &filter_sorted(&sorted, CO2_SCRUBBER).unwrap()[0];
+    decimal(&oxygen_generator_rating.product(co2_scrubber_rating))
+}
+
+pub fn parse_input(lines: &[String]) -> ParseResult<Vec<Bits>> {
+    let nums = parse::lines(lines, Bits::from_str)?;
+    let width = nums.first().map_or(0, |n| n.width);
+    if let Some(i) = nums.iter().position(|n| n.width != width) {
+        return Err(ParseError::new(0, format!("expected {} bits", width)).on_line(i));
+    }
+    Ok(nums)
 }
 
-fn part2(lines: &[String]) -> u32 {
-    let num_bits = lines[0].len();
-    let oxygen_generator_rating =
-        iterative_filter(&mut strings_to_nums(&lines.to_vec()), num_bits, true);
-    let co2_scrubber_rating =
-        iterative_filter(&mut strings_to_nums(&lines.to_vec()), num_bits, false);
-    oxygen_generator_rating * co2_scrubber_rating
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    solve_parts!(input, part)
 }
 
 pub fn run(lines: &[String]) {
-    run_parts!(lines);
+    let input = parse_input(lines).unwrap();
+    run_parts!(input);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::Random;
 
     fn get_test_input() -> Vec<String> {
         vec![
@@ -98,11 +282,153 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&get_test_input()), 198);
+        assert_eq!(part1(&parse_input(&get_test_input()).unwrap()), "198");
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&get_test_input()), 230);
+        assert_eq!(part2(&parse_input(&get_test_input()).unwrap()), "230");
+    }
+
+    #[test]
+    fn test_wide() {
+        let mut lines = vec![format!("1{}", "0".repeat(99)); 2];
+        lines.push("0".repeat(100));
+        let nums = parse_input(&lines).unwrap();
+        assert_eq!(
+            part1(&nums),
+            "401734511064747568885490523084656825330436633744949857222656"
+        );
+
+        let mut random = Random(3);
+        let lines: Vec<String> = (0..200)
+            .map(|_| format!("{:064b}", random.next_u64()))
+            .collect();
+        let nums = parse_input(&lines).unwrap();
+        let gamma = most_common_bits(&nums, 64);
+        let value = |bits: &Bits| bits.words[0] as u128;
+        assert_eq!(
+            part1(&nums),
+            (value(&gamma) * value(&gamma.complement())).to_string()
+        );
+        let oxygen = filter(&nums, OXYGEN_GENERATOR).unwrap();
+        let co2 = filter(&nums, CO2_SCRUBBER).unwrap();
+        assert_eq!(
+            part2(&nums),
+            (value(&oxygen[0]) * value(&co2[0])).to_string()
+        );
+    }
+
+    #[test]
+    fn test_filter() {
+        let nums = parse_input(&get_test_input()).unwrap();
+        let rating = |rule| filter(&nums, rule).unwrap()[0].words[0];
+        assert_eq!(rating(OXYGEN_GENERATOR), 23);
+        assert_eq!(rating(CO2_SCRUBBER), 10);
+
+        // 10 and 11 tie on the last bit
+        let nums = parse_input(&["10".to_string(), "11".to_string(), "00".to_string()]).unwrap();
+        let rule = Rule {
+            tie_break: TieBreak::KeepZeros,
+            ..OXYGEN_GENERATOR
+        };
+        assert_eq!(filter(&nums, rule).unwrap()[0].words[0], 0b10);
+        assert_eq!(filter(&nums, OXYGEN_GENERATOR).unwrap()[0].words[0], 0b11);
+
+        assert_eq!(filter(&[], CO2_SCRUBBER), Err(FilterError::NoCandidates));
+        let single = parse_input(&["101".to_string()]).unwrap();
+        assert_eq!(filter(&single, CO2_SCRUBBER).unwrap(), single);
+    }
+
+    /// The filter, recounting the candidates for every bit.
+    fn reference_filter(nums: &[Bits], rule: Rule) -> Result<Vec<Bits>, FilterError> {
+        let mut candidates = nums.to_vec();
+        let num_bits = candidates.first().ok_or(FilterError::NoCandidates)?.width;
+
+        for bit in (0..num_bits).rev() {
+            if candidates.len() == 1 {
+                break;
+            }
+            let ones = candidates.iter().filter(|n| n.get(bit)).count();
+            let zeros = candidates.len() - ones;
+            if ones == 0 || zeros == 0 {
+                continue;
+            }
+
+            let keep = match (ones.cmp(&zeros), rule.criterion) {
+                (Ordering::Equal, _) => rule.tie_break == TieBreak::KeepOnes,
+                (order, Criterion::MostCommon) => order == Ordering::Greater,
+                (order, Criterion::LeastCommon) => order == Ordering::Less,
+            };
+            candidates.retain(|n| n.get(bit) == keep);
+        }
+
+        match (candidates.len(), rule.exhausted) {
+            (1, _) | (_, Exhausted::AllSurvivors) => Ok(candidates),
+            (n, Exhausted::Error) => Err(FilterError::Ambiguous(n)),
+        }
+    }
+
+    #[test]
+    fn test_filter_sorted() {
+        let mut random = Random(48);
+        let lines: Vec<String> = (0..300)
+            .map(|_| format!("{:08b}", random.next_u64() >> 56))
+            .collect();
+        let nums = parse_input(&lines).unwrap();
+
+        for criterion in [Criterion::MostCommon, Criterion::LeastCommon] {
+            for tie_break in [TieBreak::KeepOnes, TieBreak::KeepZeros] {
+                for exhausted in [Exhausted::Error, Exhausted::AllSurvivors] {
+                    let rule = Rule {
+                        criterion,
+                        tie_break,
+                        exhausted,
+                    };
+                    assert_eq!(filter(&nums, rule), reference_filter(&nums, rule));
+                }
+            }
+        }
+
+        let mut sorted = nums.clone();
+        sorted.sort();
+        assert!(sorted.windows(2).all(|w| w[0].words[0] <= w[1].words[0]));
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let mut lines = get_test_input();
+        lines.push("10111".to_string());
+        let nums = parse_input(&lines).unwrap();
+
+        assert_eq!(filter(&nums, OXYGEN_GENERATOR).unwrap().len(), 2);
+        let rule = Rule {
+            exhausted: Exhausted::Error,
+            ..OXYGEN_GENERATOR
+        };
+        assert_eq!(filter(&nums, rule), Err(FilterError::Ambiguous(2)));
+        assert_eq!(part2(&nums), "230");
+
+        // Every line agrees on the middle bits, which are skipped
+        let nums = parse_input(&["10001".to_string(), "00000".to_string()]).unwrap();
+        assert_eq!(filter(&nums, CO2_SCRUBBER).unwrap()[0].words[0], 0);
+    }
+
+    #[test]
+    fn test_decimal() {
+        assert_eq!(decimal(&[]), "0");
+        assert_eq!(decimal(&[0, 0]), "0");
+        assert_eq!(decimal(&[u64::MAX, u64::MAX]), u128::MAX.to_string());
+        assert_eq!(decimal(&[0, 1]), (1u128 << 64).to_string());
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut lines = get_test_input();
+        lines[3] = "1011".to_string();
+        assert_eq!(parse_input(&lines).unwrap_err().line, 3);
+        lines[3] = "10121".to_string();
+        let err = parse_input(&lines).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 3));
     }
 }
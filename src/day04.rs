@@ -1,144 +1,171 @@
-#[derive(Debug)]
-struct Board {
-    board: Vec<Vec<Option<usize>>>,
+use crate::parse::{self, ParseError, ParseResult};
+use std::collections::HashMap;
+
+/// A bingo board that keeps track of its unmarked numbers per row and
+/// column, so marking a number and detecting a win take constant time.
+#[derive(Clone, Debug)]
+pub struct Board {
+    width: usize,
+    /// The cell, in row-major order, of every number on the board.
+    cells: HashMap<usize, usize>,
+    marked: Vec<bool>,
+    unmarked_in_row: Vec<usize>,
+    unmarked_in_column: Vec<usize>,
+    unmarked_sum: usize,
+    won: Option<Line>,
 }
 
 impl Board {
-    pub fn new(board: Vec<Vec<Option<usize>>>) -> Self {
-        Self { board }
-    }
+    pub fn new(rows: Vec<Vec<usize>>) -> ParseResult<Self> {
+        let width = rows.first().map_or(0, Vec::len);
+        if width == 0 {
+            return Err(ParseError::new(0, "empty board"));
+        }
 
-    pub fn mark(&mut self, number: usize) {
-        for row in &mut self.board {
-            for num in row.iter_mut() {
-                if let Some(n) = num {
-                    if *n == number {
-                        *num = None;
-                    }
-                } else {
-                };
+        let mut cells = HashMap::new();
+        for (i, row) in rows.iter().enumerate() {
+            if row.len() != width {
+                return Err(ParseError::new(0, format!("expected {} numbers", width)).on_line(i));
+            }
+            for (j, &number) in row.iter().enumerate() {
+                if cells.insert(number, i * width + j).is_some() {
+                    return Err(ParseError::new(0, format!("{} appears twice", number)).on_line(i));
+                }
             }
         }
-    }
 
-    pub fn unmarked_sum(&self) -> usize {
-        self.board.concat().iter().fold(
-            0usize,
-            |acc, x| {
-                if let Some(n) = x {
-                    acc + n
-                } else {
-                    acc
-                }
-            },
-        )
+        Ok(Self {
+            width,
+            marked: vec![false; cells.len()],
+            unmarked_in_row: vec![width; rows.len()],
+            unmarked_in_column: vec![rows.len(); width],
+            unmarked_sum: cells.keys().sum(),
+            cells,
+            won: None,
+        })
     }
 
-    pub fn bingo(&self) -> bool {
-        let row_len = self.board[0].len();
-        for row in &self.board {
-            if row.iter().all(|x| x.is_none()) {
-                return true;
-            }
+    /// Marks the number if it is on the board, returning the line it
+    /// completed, if any. A completed row takes precedence over a column.
+    pub fn mark(&mut self, number: usize) -> Option<Line> {
+        let cell = *self.cells.get(&number)?;
+        if std::mem::replace(&mut self.marked[cell], true) {
+            return None;
         }
+        self.unmarked_sum -= number;
 
-        for col in 0..row_len {
-            if self
-                .board
-                .concat()
-                .iter()
-                .skip(col)
-                .step_by(row_len)
-                .all(|x| x.is_none())
-            {
-                return true;
-            }
+        let (row, column) = (cell / self.width, cell % self.width);
+        self.unmarked_in_row[row] -= 1;
+        self.unmarked_in_column[column] -= 1;
+        let line = if self.unmarked_in_row[row] == 0 {
+            Some(Line::Row(row))
+        } else if self.unmarked_in_column[column] == 0 {
+            Some(Line::Column(column))
+        } else {
+            None
+        };
+
+        if self.won.is_none() {
+            self.won = line;
         }
-        false
+        line
     }
-}
 
-fn string_to_vec_nums(s: &str) -> Vec<Option<usize>> {
-    let mut v = Vec::<Option<usize>>::new();
-    s.split_whitespace()
-        .for_each(|x| v.push(Some(x.to_string().parse::<usize>().unwrap())));
-    v
-}
+    pub fn unmarked_sum(&self) -> usize {
+        self.unmarked_sum
+    }
 
-fn parse_lines(lines: &[String]) -> (Vec<usize>, Vec<Board>) {
-    let mut iter = lines.iter();
-    let bingo_numbers_str = &iter
-        .by_ref()
-        .take_while(|x| !x.is_empty())
-        .cloned()
-        .next()
-        .unwrap();
-    let mut boards: Vec<Board> = Vec::new();
-    let _ = &iter.next();
-
-    while iter.len() > 0 {
-        let mut board_rows: Vec<Vec<Option<usize>>> = Vec::new();
-        let board = &iter
-            .by_ref()
-            .take_while(|x| !x.is_empty())
-            .cloned()
-            .collect::<Vec<String>>();
-        board
-            .iter()
-            .for_each(|x| board_rows.push(string_to_vec_nums(x)));
-        boards.push(Board::new(board_rows));
+    /// The first line this board completed.
+    pub fn winning_line(&self) -> Option<Line> {
+        self.won
     }
+}
 
-    let bingo_numbers: Vec<usize> = bingo_numbers_str
-        .split(',')
-        .map(|x| x.parse::<usize>().unwrap())
-        .collect();
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Line {
+    Row(usize),
+    Column(usize),
+}
 
-    (bingo_numbers, boards)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Win {
+    /// Index of the board in the input.
+    pub board: usize,
+    /// Index of the winning number in the draw order.
+    pub draw: usize,
+    pub number: usize,
+    pub score: usize,
+    pub line: Line,
 }
 
-fn part1(lines: &[String]) -> usize {
-    let (numbers, mut boards) = parse_lines(lines);
+/// Plays bingo on copies of the boards, returning every win in the order
+/// they happen, with boards winning on the same number in input order.
+/// Boards that never win are left out.
+pub fn play(numbers: &[usize], boards: &[Board]) -> Vec<Win> {
+    let mut playing: Vec<Option<Board>> = boards.iter().cloned().map(Some).collect();
+    let mut wins = Vec::new();
 
-    for num in numbers {
-        boards.iter_mut().for_each(|board| board.mark(num));
-        for board in &boards {
-            if board.bingo() {
-                return board.unmarked_sum() * num;
+    for (draw, &number) in numbers.iter().enumerate() {
+        for (index, slot) in playing.iter_mut().enumerate() {
+            if let Some(board) = slot {
+                if let Some(line) = board.mark(number) {
+                    wins.push(Win {
+                        board: index,
+                        draw,
+                        number,
+                        score: board.unmarked_sum() * number,
+                        line,
+                    });
+                    *slot = None;
+                }
             }
         }
+        if wins.len() == boards.len() {
+            break;
+        }
     }
-
-    0
+    wins
 }
 
-fn part2(lines: &[String]) -> usize {
-    let (numbers, mut boards) = parse_lines(lines);
-    let mut num_iter = numbers.iter();
-    let mut last_number = 0;
+pub fn parse_input(lines: &[String]) -> ParseResult<(Vec<usize>, Vec<Board>)> {
+    let sections = parse::sections(lines);
+    let (numbers, boards) = sections
+        .split_first()
+        .ok_or_else(|| ParseError::new(0, "missing bingo numbers"))?;
 
-    while boards.len() > 1 {
-        let last_number = *num_iter.next().unwrap();
-        boards.iter_mut().for_each(|board| board.mark(last_number));
-        boards.retain(|board| !board.bingo());
-    }
+    let bingo_numbers = numbers.parse(|s| parse::integer_list(s, ","))?.remove(0);
+    let boards = boards
+        .iter()
+        .map(|board| {
+            Board::new(board.parse(parse::whitespace_list)?).map_err(|e| e.on_line(board.start))
+        })
+        .collect::<ParseResult<Vec<Board>>>()?;
 
-    let last_board = &mut boards[0];
-    while !last_board.bingo() {
-        last_number = *num_iter.next().unwrap();
-        last_board.mark(last_number);
-    }
+    Ok((bingo_numbers, boards))
+}
+
+fn part1((numbers, boards): &(Vec<usize>, Vec<Board>)) -> usize {
+    play(numbers, boards).first().map_or(0, |win| win.score)
+}
 
-    last_board.unmarked_sum() * last_number
+fn part2((numbers, boards): &(Vec<usize>, Vec<Board>)) -> usize {
+    play(numbers, boards).last().map_or(0, |win| win.score)
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    solve_parts!(input, part)
 }
 
 pub fn run(lines: &[String]) {
-    run_parts!(lines);
+    let input = parse_input(lines).unwrap();
+    run_parts!(input);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::Random;
 
     fn get_test_input() -> Vec<String> {
         vec![
@@ -165,11 +192,131 @@ mod tests {
     }
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&get_test_input()), 4512);
+        assert_eq!(part1(&parse_input(&get_test_input()).unwrap()), 4512);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&get_test_input()), 1924);
+        assert_eq!(part2(&parse_input(&get_test_input()).unwrap()), 1924);
+    }
+
+    #[test]
+    fn test_play() {
+        let (numbers, boards) = parse_input(&get_test_input()).unwrap();
+        let wins = play(&numbers, &boards);
+        assert_eq!(
+            wins,
+            [
+                Win {
+                    board: 2,
+                    draw: 11,
+                    number: 24,
+                    score: 4512,
+                    line: Line::Row(0)
+                },
+                Win {
+                    board: 0,
+                    draw: 13,
+                    number: 16,
+                    score: 2192,
+                    line: Line::Row(2)
+                },
+                Win {
+                    board: 1,
+                    draw: 14,
+                    number: 13,
+                    score: 1924,
+                    line: Line::Column(2)
+                },
+            ]
+        );
+
+        assert_eq!(boards[1].winning_line(), None);
+        let mut board = boards[1].clone();
+        assert_eq!(board.mark(99), None);
+        for n in [0, 13, 7, 10] {
+            assert_eq!(board.mark(n), None);
+        }
+        assert_eq!(board.mark(16), Some(Line::Column(2)));
+        assert_eq!(board.mark(16), None);
+        assert_eq!(board.winning_line(), Some(Line::Column(2)));
+        assert_eq!(board.unmarked_sum(), 324 - 46);
+
+        // Stopping the draw early leaves boards that never win out
+        assert_eq!(play(&numbers[..12], &boards).len(), 1);
+        assert!(play(&[], &boards).is_empty());
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut lines = get_test_input();
+        lines[10] = "19  8  7 25".to_string();
+        assert_eq!(parse_input(&lines).unwrap_err().line, 10);
+        lines[10] = "19  8  7 25 15".to_string();
+        assert_eq!(parse_input(&lines).unwrap_err().line, 10);
+    }
+
+    /// Finds every win from when each line's last number is drawn.
+    fn reference_play(numbers: &[usize], rows: &[Vec<Vec<usize>>]) -> Vec<(usize, usize, usize)> {
+        let drawn_at: HashMap<usize, usize> = numbers
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, &n)| (n, i))
+            .collect();
+        let mut wins: Vec<(usize, usize, usize)> = rows
+            .iter()
+            .enumerate()
+            .filter_map(|(index, board)| {
+                let line_done = |line: Vec<usize>| {
+                    line.iter()
+                        .map(|n| drawn_at.get(n).copied())
+                        .collect::<Option<Vec<_>>>()
+                        .map(|draws| draws.into_iter().max().unwrap())
+                };
+                let lines = (0..board.len())
+                    .map(|i| board[i].clone())
+                    .chain((0..board[0].len()).map(|j| board.iter().map(|row| row[j]).collect()));
+                let draw = lines.filter_map(line_done).min()?;
+                let unmarked: usize = board
+                    .concat()
+                    .iter()
+                    .filter(|n| drawn_at.get(n).is_none_or(|&d| d > draw))
+                    .sum();
+                Some((draw, index, unmarked * numbers[draw]))
+            })
+            .collect();
+        wins.sort();
+        wins
+    }
+
+    #[test]
+    fn test_generated() {
+        let mut random = Random(4);
+        let numbers = random.sample(1000, 600);
+        let rows: Vec<Vec<Vec<usize>>> = (0..2000)
+            .map(|_| {
+                random
+                    .sample(1000, 25)
+                    .chunks(5)
+                    .map(<[usize]>::to_vec)
+                    .collect()
+            })
+            .collect();
+        let boards: Vec<Board> = rows
+            .iter()
+            .map(|r| Board::new(r.clone()).unwrap())
+            .collect();
+
+        let wins = play(&numbers, &boards);
+        let expected = reference_play(&numbers, &rows);
+        assert!(wins.len() > 1000 && wins.len() < boards.len());
+        assert_eq!(
+            wins.iter()
+                .map(|w| (w.draw, w.board, w.score))
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert!(wins.iter().all(|w| w.number == numbers[w.draw]));
     }
 }
@@ -1,5 +1,5 @@
+use crate::parse::{self, ParseError, ParseResult};
 use std::cmp::{max, min};
-use std::num::ParseIntError;
 use std::ops::{Add, Mul, Sub};
 use std::str::FromStr;
 
@@ -57,12 +57,10 @@ impl Grid {
 }
 
 impl FromStr for Point {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let coords = s.trim().split(',').collect::<Vec<&str>>();
-        let x = isize::from_str(coords[0])?;
-        let y = isize::from_str(coords[1])?;
+        let (x, y) = parse::pair(s, ",", parse::number, parse::number)?;
         Ok(Point { x, y })
     }
 }
@@ -100,44 +98,40 @@ impl Mul<isize> for Point {
     }
 }
 
-fn parse_lines(lines: &[String]) -> Vec<(Point, Point)> {
-    let mut points: Vec<(Point, Point)> = Vec::new();
-    lines.iter().for_each(|line| {
-        let point_strs = line.split("->").collect::<Vec<&str>>();
-        let point_pair = (
-            Point::from_str(point_strs[0]).expect("failed to parse point"),
-            Point::from_str(point_strs[1]).expect("failed to parse point"),
-        );
-        points.push(point_pair);
-    });
-    points
+fn parse_lines(lines: &[String]) -> ParseResult<Vec<(Point, Point)>> {
+    parse::lines(lines, |line| {
+        parse::pair(line, "->", Point::from_str, Point::from_str)
+    })
 }
 
 fn straight_path(ps: (Point, Point)) -> bool {
     ps.0.x == ps.1.x || ps.0.y == ps.1.y
 }
 
-fn count_points(ps: Vec<(Point, Point)>) -> usize {
+fn count_points<'a>(ps: impl Iterator<Item = &'a (Point, Point)>) -> usize {
     let mut grid = Grid::new();
-    ps.iter().for_each(|&p| {
+    ps.for_each(|&p| {
         grid.insert_line(p);
     });
     grid.count_intersections()
 }
 
-fn part1(lines: &[String]) -> usize {
-    let mut ps = parse_lines(lines);
-    ps.retain(|p| straight_path(*p));
-    count_points(ps)
+fn part1(ps: &[(Point, Point)]) -> usize {
+    count_points(ps.iter().filter(|p| straight_path(**p)))
+}
+
+fn part2(ps: &[(Point, Point)]) -> usize {
+    count_points(ps.iter())
 }
 
-fn part2(lines: &[String]) -> usize {
-    let ps = parse_lines(lines);
-    count_points(ps)
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let ps = parse_lines(lines)?;
+    solve_parts!(ps, part)
 }
 
 pub fn run(lines: &[String]) {
-    run_parts!(lines);
+    let ps = parse_lines(lines).unwrap();
+    run_parts!(ps);
 }
 
 #[cfg(test)]
@@ -160,11 +154,11 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&get_test_input()), 5);
+        assert_eq!(part1(&parse_lines(&get_test_input()).unwrap()), 5);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&get_test_input()), 12);
+        assert_eq!(part2(&parse_lines(&get_test_input()).unwrap()), 12);
     }
 }
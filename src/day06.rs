@@ -1,4 +1,6 @@
-fn step(states: &mut Vec<usize>) {
+use crate::parse::{self, ParseError, ParseResult};
+
+fn step(states: &mut [usize]) {
     states.rotate_left(1);
     states[6] += states[8];
 }
@@ -19,16 +21,23 @@ fn part2(initial_state: &[usize]) -> usize {
     simulate(initial_state, 256)
 }
 
-fn parse_input(s: &str) -> Vec<usize> {
-    let mut state: Vec<usize> = Vec::new();
-    s.split(',').for_each(|x| state.push(x.parse().unwrap()));
+fn parse_input(s: &str) -> ParseResult<Vec<usize>> {
+    let state: Vec<usize> = parse::list(s, ",", |timer| match parse::number(timer)? {
+        t if t > 8 => Err(ParseError::new(0, format!("timer {} is above 8", t))),
+        t => Ok(t),
+    })?;
     let mut states: Vec<usize> = vec![0; 9];
     state.iter().for_each(|x| states[*x] += 1);
-    states
+    Ok(states)
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let init_state = parse_input(parse::first_line(lines)?)?;
+    solve_parts!(init_state, part)
 }
 
 pub fn run(lines: &[String]) {
-    let init_state = parse_input(&lines[0]);
+    let init_state = parse_input(&lines[0]).unwrap();
     run_parts!(init_state);
 }
 
@@ -42,12 +51,15 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(simulate(&parse_input(&get_test_input()), 18), 26);
-        assert_eq!(simulate(&parse_input(&get_test_input()), 80), 5934);
+        assert_eq!(simulate(&parse_input(&get_test_input()).unwrap(), 18), 26);
+        assert_eq!(simulate(&parse_input(&get_test_input()).unwrap(), 80), 5934);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(simulate(&parse_input(&get_test_input()), 256), 26984457539);
+        assert_eq!(
+            simulate(&parse_input(&get_test_input()).unwrap(), 256),
+            26984457539
+        );
     }
 }
@@ -1,3 +1,5 @@
+use crate::parse::{self, ParseResult};
+
 fn part1(positions: &[isize]) -> isize {
     let mut ps = positions.to_owned();
     ps.sort_unstable();
@@ -18,15 +20,17 @@ fn part2(positions: &[isize]) -> isize {
     best
 }
 
-fn parse_input(input: &str) -> Vec<isize> {
-    input
-        .split(',')
-        .map(|x| x.parse::<isize>().unwrap())
-        .collect()
+fn parse_input(input: &str) -> ParseResult<Vec<isize>> {
+    parse::integer_list(input, ",")
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(parse::first_line(lines)?)?;
+    solve_parts!(input, part)
 }
 
 pub fn run(lines: &[String]) {
-    let input = parse_input(&lines[0]);
+    let input = parse_input(&lines[0]).unwrap();
     run_parts!(input);
 }
 
@@ -40,11 +44,11 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&parse_input(&get_test_input())), 37);
+        assert_eq!(part1(&parse_input(&get_test_input()).unwrap()), 37);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&parse_input(&get_test_input())), 168);
+        assert_eq!(part2(&parse_input(&get_test_input()).unwrap()), 168);
     }
 }
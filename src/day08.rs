@@ -1,3 +1,6 @@
+use crate::parse::{self, ParseResult};
+use crate::trace::Level;
+
 fn part1(s: &[Vec<&str>]) -> usize {
     let digits: Vec<&str> = s.iter().map(|x| x[1]).collect();
     let mut sum = 0;
@@ -41,6 +44,7 @@ fn parse_one_entry(entry: Vec<&str>) -> usize {
         d7 = seven;
         d8 = eight;
     }
+    event!(Level::Debug, "unique", d1 = d1, d4 = d4, d7 = d7, d8 = d8);
 
     // The only digit that 4 shares all segments with is 9
     let d9 = unknowns
@@ -49,6 +53,7 @@ fn parse_one_entry(entry: Vec<&str>) -> usize {
         .unwrap()
         .to_owned();
     unknowns.retain(|x| *x != d9);
+    event!(Level::Debug, "deduced", digit = 9, segments = d9);
 
     // The only digit now that shares 2 segments with 4 is 2
     let d2 = unknowns
@@ -57,6 +62,7 @@ fn parse_one_entry(entry: Vec<&str>) -> usize {
         .unwrap()
         .to_owned();
     unknowns.retain(|x| *x != d2);
+    event!(Level::Debug, "deduced", digit = 2, segments = d2);
 
     // The only digit now that shares 3 segments with 2 is 5
     let d5 = unknowns
@@ -65,10 +71,12 @@ fn parse_one_entry(entry: Vec<&str>) -> usize {
         .unwrap()
         .to_owned();
     unknowns.retain(|x| *x != d5);
+    event!(Level::Debug, "deduced", digit = 5, segments = d5);
 
     // 3 is the only remaining digit with 5 segments
     let d3 = unknowns.iter().find(|x| x.len() == 5).unwrap().to_owned();
     unknowns.retain(|x| *x != d3);
+    event!(Level::Debug, "deduced", digit = 3, segments = d3);
 
     // The only digit that 5 shares all segments with is 6
     let d6 = unknowns
@@ -77,9 +85,11 @@ fn parse_one_entry(entry: Vec<&str>) -> usize {
         .unwrap()
         .to_owned();
     unknowns.retain(|x| *x != d6);
+    event!(Level::Debug, "deduced", digit = 6, segments = d6);
 
     // 0 is the final digit
     let d0 = unknowns.pop().unwrap();
+    event!(Level::Debug, "deduced", digit = 0, segments = d0);
 
     let digits = [d0, d1, d2, d3, d4, d5, d6, d7, d8, d9];
 
@@ -87,24 +97,25 @@ fn parse_one_entry(entry: Vec<&str>) -> usize {
 
     ret += digits
         .iter()
-        .position(|x| sorted_equal(*x, output[0]))
+        .position(|x| sorted_equal(x, output[0]))
         .unwrap()
         * 1000;
     ret += digits
         .iter()
-        .position(|x| sorted_equal(*x, output[1]))
+        .position(|x| sorted_equal(x, output[1]))
         .unwrap()
         * 100;
     ret += digits
         .iter()
-        .position(|x| sorted_equal(*x, output[2]))
+        .position(|x| sorted_equal(x, output[2]))
         .unwrap()
         * 10;
     ret += digits
         .iter()
-        .position(|x| sorted_equal(*x, output[3]))
+        .position(|x| sorted_equal(x, output[3]))
         .unwrap();
 
+    event!(Level::Debug, "decoded", output = ret);
     ret
 }
 
@@ -112,12 +123,20 @@ fn part2(s: &[Vec<&str>]) -> usize {
     s.iter().map(|x| parse_one_entry(x.to_vec())).sum()
 }
 
-fn parse_input(input: &[String]) -> Vec<Vec<&str>> {
-    input.iter().map(|x| x.split(" | ").collect()).collect()
+fn parse_input(input: &[String]) -> ParseResult<Vec<Vec<&str>>> {
+    parse::lines(input, |x| {
+        let (signals, output) = parse::split_pair(x, " | ")?;
+        Ok(vec![signals, output])
+    })
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    solve_parts!(input, part)
 }
 
 pub fn run(lines: &[String]) {
-    let input = parse_input(lines);
+    let input = parse_input(lines).unwrap();
     run_parts!(input);
 }
 
@@ -142,11 +161,11 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&parse_input(&get_test_input())), 26);
+        assert_eq!(part1(&parse_input(&get_test_input()).unwrap()), 26);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&parse_input(&get_test_input())), 61229);
+        assert_eq!(part2(&parse_input(&get_test_input()).unwrap()), 61229);
     }
 }
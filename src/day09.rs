@@ -1,3 +1,5 @@
+use crate::parse::{self, ParseResult};
+use crate::trace::Level;
 use ndarray::{s, Array, Array2, ArrayView2};
 use std::collections::VecDeque;
 
@@ -65,7 +67,9 @@ fn flood_fill(grid: &mut Array2<bool>, node: (usize, usize)) -> usize {
     let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
     queue.push_back(node);
     let mut sum = 0;
+    let mut max_queue = queue.len();
     while !queue.is_empty() {
+        max_queue = max_queue.max(queue.len());
         let (i, j) = queue.pop_front().unwrap();
         let n = grid.get_mut((i, j));
         if let Some(a) = n {
@@ -87,6 +91,13 @@ fn flood_fill(grid: &mut Array2<bool>, node: (usize, usize)) -> usize {
             }
         }
     }
+    event!(
+        Level::Debug,
+        "basin",
+        start = node,
+        size = sum,
+        max_queue = max_queue
+    );
     sum
 }
 
@@ -105,29 +116,34 @@ fn part2(grid: &Array2<Option<usize>>) -> usize {
     }
     let mut basins: Vec<usize> = minima.iter().map(|m| flood_fill(&mut caves, *m)).collect();
     basins.sort_unstable();
+    event!(
+        Level::Info,
+        "basins",
+        count = basins.len(),
+        minima = minima.len()
+    );
     basins.iter().rev().take(3).product()
 }
 
-pub fn parse_input(input: &[String]) -> Array2<Option<usize>> {
-    let mut grid = Array2::<Option<usize>>::default((input.len() + 2, input[0].len() + 2));
-
-    let v = input
-        .iter()
-        .map(|x| {
-            x.chars()
-                .map(|c| Some(c.to_digit(10).unwrap() as usize))
-                .collect::<Vec<Option<usize>>>()
-        })
-        .flatten()
-        .collect();
-
-    let mut grid_slice = grid.slice_mut(s![1..input.len() + 1, 1..input[0].len() + 1]);
-    grid_slice.assign(&Array2::from_shape_vec((input.len(), input[0].len()), v).unwrap());
-    grid
+pub fn parse_input(input: &[String]) -> ParseResult<Array2<Option<usize>>> {
+    let digits = parse::digit_grid(input)?;
+    let (rows, cols) = (digits.len(), digits.first().map_or(0, Vec::len));
+    let mut grid = Array2::<Option<usize>>::default((rows + 2, cols + 2));
+
+    let v = digits.into_iter().flatten().map(Some).collect();
+
+    let mut grid_slice = grid.slice_mut(s![1..rows + 1, 1..cols + 1]);
+    grid_slice.assign(&Array2::from_shape_vec((rows, cols), v).unwrap());
+    Ok(grid)
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    solve_parts!(input, part)
 }
 
 pub fn run(lines: &[String]) {
-    let input = parse_input(lines);
+    let input = parse_input(lines).unwrap();
     run_parts!(input);
 }
 
@@ -147,11 +163,11 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&parse_input(&get_test_input())), 15);
+        assert_eq!(part1(&parse_input(&get_test_input()).unwrap()), 15);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&parse_input(&get_test_input())), 1134);
+        assert_eq!(part2(&parse_input(&get_test_input()).unwrap()), 1134);
     }
 }
@@ -1,3 +1,5 @@
+use crate::parse::ParseResult;
+
 fn matching_delim(c: char) -> char {
     match c {
         '(' => ')',
@@ -54,8 +56,7 @@ fn part2(input: &[String]) -> usize {
         .map(|x| parse_brackets(x))
         .filter(|x| x.0.is_none())
         .filter(|x| x.1.is_some())
-        .map(|x| x.1)
-        .flatten()
+        .filter_map(|x| x.1)
         .map(|stack| {
             stack.iter().rev().fold(0, |acc, c| match c {
                 '(' => acc * 5 + 1,
@@ -70,6 +71,10 @@ fn part2(input: &[String]) -> usize {
     complete_scores[complete_scores.len() / 2]
 }
 
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    solve_parts!(lines, part)
+}
+
 pub fn run(lines: &[String]) {
     run_parts!(lines);
 }
@@ -1,4 +1,6 @@
 use crate::day09;
+use crate::parse::ParseResult;
+use crate::trace::Level;
 use ndarray::{s, Array2, ArrayViewMut2};
 use std::collections::VecDeque;
 
@@ -15,7 +17,7 @@ fn increment_energies(grid: &mut ArrayViewMut2<Option<usize>>) -> VecDeque<(usiz
     queue
 }
 
-fn step(grid: &mut ArrayViewMut2<Option<usize>>) -> usize {
+pub fn step(grid: &mut ArrayViewMut2<Option<usize>>) -> usize {
     let mut queue = VecDeque::new();
     queue.append(&mut increment_energies(grid));
     let mut total_flashes = 0;
@@ -44,6 +46,7 @@ fn step(grid: &mut ArrayViewMut2<Option<usize>>) -> usize {
         }
     });
 
+    event!(Level::Debug, "step", flashes = total_flashes);
     total_flashes
 }
 
@@ -62,13 +65,19 @@ fn part2(grid: &Array2<Option<usize>>) -> usize {
     loop {
         i += 1;
         if step(&mut g.view_mut()) == 100 {
+            event!(Level::Info, "synchronised", step = i);
             return i;
         }
     }
 }
 
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = day09::parse_input(lines)?;
+    solve_parts!(input, part)
+}
+
 pub fn run(lines: &[String]) {
-    let input = day09::parse_input(lines);
+    let input = day09::parse_input(lines).unwrap();
     run_parts!(input);
 }
 
@@ -93,11 +102,11 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&day09::parse_input(&get_test_input())), 1656);
+        assert_eq!(part1(&day09::parse_input(&get_test_input()).unwrap()), 1656);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&day09::parse_input(&get_test_input())), 195);
+        assert_eq!(part2(&day09::parse_input(&get_test_input()).unwrap()), 195);
     }
 }
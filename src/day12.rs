@@ -1,3 +1,6 @@
+use crate::parse::{self, ParseResult};
+use crate::trace::Level;
+
 fn dfs(
     cave: &str,
     connections: &[Vec<&str>],
@@ -30,6 +33,13 @@ fn dfs(
     if twice == Some(&"".to_string()) {
         for other_cave in connected_caves.iter() {
             if visited_caves.contains(other_cave) && other_cave != "start" {
+                event!(
+                    Level::Debug,
+                    "revisit",
+                    cave = other_cave,
+                    from = cave,
+                    visited = visited_caves,
+                );
                 sum += dfs(other_cave, connections, visited_caves, Some(other_cave));
             }
         }
@@ -44,27 +54,31 @@ fn dfs(
 }
 
 fn part1(connections: &[Vec<&str>]) -> usize {
-    dfs(&"start".to_string(), connections, &mut Vec::new(), None)
+    let paths = dfs("start", connections, &mut Vec::new(), None);
+    event!(Level::Info, "paths", twice = false, count = paths);
+    paths
 }
 
 fn part2(connections: &[Vec<&str>]) -> usize {
-    dfs(
-        &"start".to_string(),
-        connections,
-        &mut Vec::new(),
-        Some(&"".to_string()),
-    )
+    let paths = dfs("start", connections, &mut Vec::new(), Some(&"".to_string()));
+    event!(Level::Info, "paths", twice = true, count = paths);
+    paths
 }
 
-fn parse_input(lines: &[String]) -> Vec<Vec<&str>> {
-    lines
-        .iter()
-        .map(|x| x.split('-').collect::<Vec<_>>())
-        .collect()
+fn parse_input(lines: &[String]) -> ParseResult<Vec<Vec<&str>>> {
+    parse::lines(lines, |x| {
+        let (a, b) = parse::split_pair(x, "-")?;
+        Ok(vec![a, b])
+    })
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    solve_parts!(input, part)
 }
 
 pub fn run(lines: &[String]) {
-    let input = parse_input(lines);
+    let input = parse_input(lines).unwrap();
     run_parts!(input);
 }
 
@@ -123,15 +137,15 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&parse_input(&get_test_input_small())), 10);
-        assert_eq!(part1(&parse_input(&get_test_input_medium())), 19);
-        assert_eq!(part1(&parse_input(&get_test_input_large())), 226);
+        assert_eq!(part1(&parse_input(&get_test_input_small()).unwrap()), 10);
+        assert_eq!(part1(&parse_input(&get_test_input_medium()).unwrap()), 19);
+        assert_eq!(part1(&parse_input(&get_test_input_large()).unwrap()), 226);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&parse_input(&get_test_input_small())), 36);
-        assert_eq!(part2(&parse_input(&get_test_input_medium())), 103);
-        assert_eq!(part2(&parse_input(&get_test_input_large())), 3509);
+        assert_eq!(part2(&parse_input(&get_test_input_small()).unwrap()), 36);
+        assert_eq!(part2(&parse_input(&get_test_input_medium()).unwrap()), 103);
+        assert_eq!(part2(&parse_input(&get_test_input_large()).unwrap()), 3509);
     }
 }
@@ -1,25 +1,24 @@
-use std::{fs, ops::BitOr, str::FromStr, string::ParseError};
+use crate::parse::{self, ParseError, ParseResult};
+use crate::trace::Level;
+use std::{fs, ops::BitOr, str::FromStr};
 
 use ndarray::{Array2, ArrayView, Axis, Slice};
 
-struct Coord {
-    x: usize,
-    y: usize,
+pub struct Coord {
+    pub x: usize,
+    pub y: usize,
 }
 
 impl FromStr for Coord {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let coord: Vec<&str> = s.split(',').collect();
-        Ok(Coord {
-            x: coord[0].parse().unwrap(),
-            y: coord[1].parse().unwrap(),
-        })
+        let (x, y) = parse::pair(s, ",", parse::number, parse::number)?;
+        Ok(Coord { x, y })
     }
 }
 
-fn array_from_coords(coords: &[Coord]) -> Array2<usize> {
+pub fn array_from_coords(coords: &[Coord]) -> Array2<usize> {
     let x_max = coords.iter().max_by(|c1, c2| c1.x.cmp(&c2.x)).unwrap().x + 1;
     let y_max = coords.iter().max_by(|c1, c2| c1.y.cmp(&c2.y)).unwrap().y + 1;
     let mut arr = Array2::from_shape_fn((y_max, x_max), |(_, _)| 0);
@@ -30,7 +29,7 @@ fn array_from_coords(coords: &[Coord]) -> Array2<usize> {
     arr
 }
 
-fn perform_fold(arr: &mut Array2<usize>, fold: &Coord) {
+pub fn perform_fold(arr: &mut Array2<usize>, fold: &Coord) {
     let axis;
     let index;
     if fold.x == 0 {
@@ -41,6 +40,7 @@ fn perform_fold(arr: &mut Array2<usize>, fold: &Coord) {
         index = fold.x;
     }
 
+    let before = arr.dim();
     arr.remove_index(axis, index);
     let (a1, a2) = arr.view().split_at(axis, index);
 
@@ -58,6 +58,15 @@ fn perform_fold(arr: &mut Array2<usize>, fold: &Coord) {
     }
 
     *arr = stationary.bitor(folded.slice_axis(axis, Slice::new(0, None, -1)));
+    event!(
+        Level::Info,
+        "fold",
+        axis = if axis == Axis(0) { 'y' } else { 'x' },
+        index = index,
+        before = before,
+        after = arr.dim(),
+        dots = arr.sum(),
+    );
 }
 
 fn part1(input: &(Vec<Coord>, Vec<Coord>)) -> usize {
@@ -66,12 +75,7 @@ fn part1(input: &(Vec<Coord>, Vec<Coord>)) -> usize {
     arr.sum()
 }
 
-fn part2(input: &(Vec<Coord>, Vec<Coord>)) -> &str {
-    let mut arr = array_from_coords(&input.0);
-    for fold in &input.1 {
-        perform_fold(&mut arr, fold);
-    }
-
+pub fn render(arr: &Array2<usize>) -> String {
     let mut output: String = String::new();
     for row in arr.rows() {
         for col in row {
@@ -83,45 +87,53 @@ fn part2(input: &(Vec<Coord>, Vec<Coord>)) -> &str {
         }
         output += "\n";
     }
-    fs::write("outputs/13.txt", output).expect("Unable to write output");
+    output
+}
 
+fn part2(input: &(Vec<Coord>, Vec<Coord>)) -> String {
+    let mut arr = array_from_coords(&input.0);
+    for fold in &input.1 {
+        perform_fold(&mut arr, fold);
+    }
+    render(&arr)
+}
+
+/// The part 2 answer is drawn in dots, so `run` writes it to a file
+/// instead of the results table.
+fn write_part2(input: &(Vec<Coord>, Vec<Coord>)) -> &'static str {
+    fs::write("outputs/13.txt", part2(input)).expect("Unable to write output");
     "See outputs/13.txt"
 }
 
-fn parse_input(input: &[String]) -> (Vec<Coord>, Vec<Coord>) {
-    let mut input_iter = input.split(|x| x.is_empty());
-    let coords_str = input_iter.next().unwrap();
-    let folds_str = input_iter.next().unwrap();
-
-    let coords_vec = coords_str
-        .iter()
-        .map(|c| Coord::from_str(c).unwrap())
-        .collect();
-
-    let folds_vec = folds_str
-        .iter()
-        .map(|s| {
-            let mut fold = s.split_whitespace().nth(2).unwrap().split('=');
-            if fold.next().unwrap() == "x" {
-                Coord {
-                    x: fold.next().unwrap().parse().unwrap(),
-                    y: 0,
-                }
-            } else {
-                Coord {
-                    x: 0,
-                    y: fold.next().unwrap().parse().unwrap(),
-                }
-            }
-        })
-        .collect();
+fn parse_fold(s: &str) -> ParseResult<Coord> {
+    let prefix = "fold along ";
+    let fold = parse::tag(s, prefix)?;
+    match parse::key_value(fold, parse::number).map_err(|e| e.shifted(prefix.len()))? {
+        ("x", x) => Ok(Coord { x, y: 0 }),
+        ("y", y) => Ok(Coord { x: 0, y }),
+        (axis, _) => Err(ParseError::new(
+            prefix.len(),
+            format!("unknown axis {:?}", axis),
+        )),
+    }
+}
+
+pub fn parse_input(input: &[String]) -> ParseResult<(Vec<Coord>, Vec<Coord>)> {
+    let sections = parse::n_sections(input, 2)?;
+    let coords_vec = sections[0].parse(Coord::from_str)?;
+    let folds_vec = sections[1].parse(parse_fold)?;
+
+    Ok((coords_vec, folds_vec))
+}
 
-    (coords_vec, folds_vec)
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    solve_parts!(input, part)
 }
 
 pub fn run(lines: &[String]) {
-    let input = parse_input(lines);
-    run_parts!(input);
+    let input = parse_input(lines).unwrap();
+    run_parts!(input; part1, write_part2);
 }
 
 #[cfg(test)]
@@ -156,11 +168,14 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&parse_input(&get_test_input())), 17);
+        assert_eq!(part1(&parse_input(&get_test_input()).unwrap()), 17);
     }
 
     #[test]
     fn test_part2() {
-        part2(&parse_input(&get_test_input()));
+        assert_eq!(
+            part2(&parse_input(&get_test_input()).unwrap()),
+            "#####\n#   #\n#   #\n#   #\n#####\n     \n     \n"
+        );
     }
 }
@@ -1,10 +1,12 @@
+use crate::parse::{self, ParseResult};
+use crate::trace::Level;
 use std::collections::HashMap;
 
-type Elements = HashMap<char, usize>;
-type Rules = HashMap<String, String>;
-type Polymer = HashMap<String, usize>;
+pub type Elements = HashMap<char, usize>;
+pub type Rules = HashMap<String, String>;
+pub type Polymer = HashMap<String, usize>;
 
-fn step(polymer: &mut Polymer, rules: &Rules, elements: &mut Elements) {
+pub fn step(polymer: &mut Polymer, rules: &Rules, elements: &mut Elements) {
     let pairs = polymer.keys().cloned().collect::<Vec<_>>();
     let mut new_pairs = Polymer::new();
 
@@ -26,6 +28,13 @@ fn step(polymer: &mut Polymer, rules: &Rules, elements: &mut Elements) {
     for (k, v) in new_pairs {
         polymer.insert(k, v);
     }
+
+    event!(
+        Level::Debug,
+        "step",
+        pairs = polymer.values().filter(|&&n| n > 0).count(),
+        length = elements.values().sum::<usize>(),
+    );
 }
 
 fn run_steps(steps: usize, p: &mut Polymer, r: &Rules, e: &mut Elements) -> usize {
@@ -48,21 +57,20 @@ fn run_steps(steps: usize, p: &mut Polymer, r: &Rules, e: &mut Elements) -> usiz
     most - least
 }
 
-fn part1(lines: &[String]) -> usize {
-    let (mut polymer, rules, mut elements) = parse_lines2(lines);
+fn part1(input: &(Polymer, Rules, Elements)) -> usize {
+    let (mut polymer, rules, mut elements) = input.clone();
     run_steps(10, &mut polymer, &rules, &mut elements)
 }
 
-fn part2(lines: &[String]) -> usize {
-    let (mut polymer, rules, mut elements) = parse_lines2(lines);
+fn part2(input: &(Polymer, Rules, Elements)) -> usize {
+    let (mut polymer, rules, mut elements) = input.clone();
     run_steps(40, &mut polymer, &rules, &mut elements)
 }
 
-fn parse_lines2(lines: &[String]) -> (Polymer, Rules, Elements) {
-    let mut input_iter = lines.split(|x| x.is_empty());
+pub fn parse_lines2(lines: &[String]) -> ParseResult<(Polymer, Rules, Elements)> {
+    let sections = parse::n_sections(lines, 2)?;
 
-    let polymer_template = input_iter.next().unwrap()[0].clone();
-    let pair_insertions_input = input_iter.next().unwrap();
+    let polymer_template = sections[0].first()?;
 
     let mut polymer = Polymer::new();
     let mut elements = Elements::new();
@@ -76,20 +84,25 @@ fn parse_lines2(lines: &[String]) -> (Polymer, Rules, Elements) {
         *elements.entry(c).or_default() += 1;
     }
 
-    let mut pair_insertion_rules = Rules::new();
+    let pair_insertion_rules = sections[1]
+        .parse(|line| {
+            let (pair, insertion) = parse::split_pair(line, " -> ")?;
+            Ok((pair.to_string(), insertion.to_string()))
+        })?
+        .into_iter()
+        .collect::<Rules>();
 
-    pair_insertions_input.iter().for_each(|line| {
-        let rule_str = line.split(" -> ").collect::<Vec<_>>();
-        *pair_insertion_rules
-            .entry(rule_str[0].to_string())
-            .or_default() = rule_str[1].to_string();
-    });
+    Ok((polymer, pair_insertion_rules, elements))
+}
 
-    (polymer, pair_insertion_rules, elements)
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_lines2(lines)?;
+    solve_parts!(input, part)
 }
 
 pub fn run(lines: &[String]) {
-    run_parts!(lines);
+    let input = parse_lines2(lines).unwrap();
+    run_parts!(input);
 }
 
 #[cfg(test)]
@@ -120,7 +133,7 @@ mod tests {
 
     #[test]
     fn test_steps() {
-        let (mut polymer, rules, mut elements) = parse_lines2(&get_test_input());
+        let (mut polymer, rules, mut elements) = parse_lines2(&get_test_input()).unwrap();
 
         let step_0 = "NNCB";
         let step_1 = "NCNBCHB";
@@ -304,11 +317,14 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&get_test_input()), 1588);
+        assert_eq!(part1(&parse_lines2(&get_test_input()).unwrap()), 1588);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&get_test_input()), 2188189693529);
+        assert_eq!(
+            part2(&parse_lines2(&get_test_input()).unwrap()),
+            2188189693529
+        );
     }
 }
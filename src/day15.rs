@@ -2,16 +2,20 @@
 // https://doc.rust-lang.org/std/collections/binary_heap/index.html
 // Entire Dijkstra implementation
 
+use crate::parse::{self, ParseResult};
+use crate::trace::Level;
 use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::Debug;
+use std::hash::Hash;
 
 #[derive(Copy, Clone, Eq, PartialEq)]
-struct State {
+struct State<N> {
     cost: usize,
-    position: usize,
+    position: N,
 }
 
-impl Ord for State {
+impl<N: Ord> Ord for State<N> {
     fn cmp(&self, other: &Self) -> Ordering {
         other
             .cost
@@ -20,54 +24,121 @@ impl Ord for State {
     }
 }
 
-impl PartialOrd for State {
+impl<N: Ord> PartialOrd for State<N> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-#[derive(Debug)]
-struct Edge {
-    node: usize,
-    cost: usize,
+/// The best known cost of every node `dijkstra` has reached.
+pub trait Distances<N> {
+    /// The cost of reaching `node`, or `usize::MAX` if it has not been reached.
+    fn get(&self, node: &N) -> usize;
+    fn set(&mut self, node: N, cost: usize);
 }
-fn shortest_path(adj_list: &[Vec<Edge>], start: usize, goal: usize) -> Option<usize> {
-    let mut dist: Vec<_> = (0..adj_list.len()).map(|_| usize::MAX).collect();
 
+/// Distances of arbitrary nodes, for graphs that are discovered as they go.
+impl<N: Eq + Hash> Distances<N> for HashMap<N, usize> {
+    fn get(&self, node: &N) -> usize {
+        *HashMap::get(self, node).unwrap_or(&usize::MAX)
+    }
+
+    fn set(&mut self, node: N, cost: usize) {
+        self.insert(node, cost);
+    }
+}
+
+/// Distances of nodes numbered `0..len`, which must start out as `usize::MAX`.
+impl Distances<usize> for Vec<usize> {
+    fn get(&self, node: &usize) -> usize {
+        self[*node]
+    }
+
+    fn set(&mut self, node: usize, cost: usize) {
+        self[node] = cost;
+    }
+}
+
+/// Dijkstra over an implicit graph: `neighbors` yields each node reachable
+/// from a node together with the cost of getting there.
+pub fn dijkstra<N, I>(
+    start: N,
+    is_goal: impl Fn(&N) -> bool,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut dist: impl Distances<N>,
+) -> Option<usize>
+where
+    N: Clone + Ord + Debug,
+    I: IntoIterator<Item = (N, usize)>,
+{
     let mut heap = BinaryHeap::new();
 
-    dist[start] = 0;
+    dist.set(start.clone(), 0);
     heap.push(State {
         cost: 0,
         position: start,
     });
 
+    let mut pops = 0;
+    let mut max_heap = heap.len();
     while let Some(State { cost, position }) = heap.pop() {
-        if position == goal {
+        pops += 1;
+        event!(
+            Level::Debug,
+            "pop",
+            cost = cost,
+            position = position,
+            heap = heap.len()
+        );
+
+        if is_goal(&position) {
+            event!(
+                Level::Info,
+                "done",
+                cost = cost,
+                pops = pops,
+                max_heap = max_heap
+            );
             return Some(cost);
         }
 
-        if cost > dist[position] {
+        if cost > dist.get(&position) {
             continue;
         }
 
-        for edge in &adj_list[position] {
+        for (node, edge_cost) in neighbors(&position) {
             let next = State {
-                cost: cost + edge.cost,
-                position: edge.node,
+                cost: cost + edge_cost,
+                position: node,
             };
 
-            if next.cost < dist[next.position] {
+            if next.cost < dist.get(&next.position) {
+                dist.set(next.position.clone(), next.cost);
                 heap.push(next);
-                dist[next.position] = next.cost;
+                max_heap = max_heap.max(heap.len());
             }
         }
     }
     None
 }
 
-fn part1(lines: &[String]) -> usize {
-    let (graph, end) = grid_to_graph(parse_input(lines));
+#[derive(Debug)]
+struct Edge {
+    node: usize,
+    cost: usize,
+}
+
+fn shortest_path(adj_list: &[Vec<Edge>], start: usize, goal: usize) -> Option<usize> {
+    dijkstra(
+        start,
+        |&position| position == goal,
+        |&position| adj_list[position].iter().map(|e| (e.node, e.cost)),
+        vec![usize::MAX; adj_list.len()],
+    )
+}
+
+fn part1(grid: &[Vec<usize>]) -> usize {
+    let (graph, end) = grid_to_graph(grid.to_vec());
     shortest_path(&graph, 0, end).unwrap()
 }
 
@@ -75,17 +146,15 @@ fn concat_grids_horizontal(g1: &[Vec<usize>], g2: &[Vec<usize>]) -> Vec<Vec<usiz
     let mut ret = Vec::new();
 
     for (i, row) in g1.iter().enumerate() {
-        ret.push(vec![row.clone(), g2[i].clone()].concat());
+        ret.push([row.clone(), g2[i].clone()].concat());
     }
 
     ret
 }
 
-fn part2(lines: &[String]) -> usize {
-    let grid = parse_input(lines);
-
-    let inc = |g: &Vec<Vec<usize>>, x: usize| -> Vec<Vec<usize>> {
-        let mut ret = g.clone();
+fn part2(grid: &[Vec<usize>]) -> usize {
+    let inc = |g: &[Vec<usize>], x: usize| -> Vec<Vec<usize>> {
+        let mut ret = g.to_vec();
         ret.iter_mut().for_each(|row| {
             row.iter_mut().for_each(|val| {
                 *val += x;
@@ -97,10 +166,10 @@ fn part2(lines: &[String]) -> usize {
         ret
     };
 
-    let mut big_grid_row = grid.clone();
+    let mut big_grid_row = grid.to_vec();
 
     for i in 1..5 {
-        big_grid_row = concat_grids_horizontal(&big_grid_row, &inc(&grid, i));
+        big_grid_row = concat_grids_horizontal(&big_grid_row, &inc(grid, i));
     }
 
     let mut big_grid = big_grid_row.clone();
@@ -159,19 +228,18 @@ fn grid_to_graph(grid: Vec<Vec<usize>>) -> (Vec<Vec<Edge>>, usize) {
     (graph, last_index)
 }
 
-fn parse_input(input: &[String]) -> Vec<Vec<usize>> {
-    input
-        .iter()
-        .map(|x| {
-            x.chars()
-                .map(|c| c.to_digit(10).unwrap() as usize)
-                .collect::<Vec<_>>()
-        })
-        .collect()
+fn parse_input(input: &[String]) -> ParseResult<Vec<Vec<usize>>> {
+    parse::digit_grid(input)
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    solve_parts!(input, part)
 }
 
 pub fn run(lines: &[String]) {
-    run_parts!(lines);
+    let input = parse_input(lines).unwrap();
+    run_parts!(input);
 }
 
 #[cfg(test)]
@@ -195,11 +263,11 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&get_test_input()), 40);
+        assert_eq!(part1(&parse_input(&get_test_input()).unwrap()), 40);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&get_test_input()), 315);
+        assert_eq!(part2(&parse_input(&get_test_input()).unwrap()), 315);
     }
 }
@@ -1,3 +1,5 @@
+use crate::parse::{self, ParseError, ParseResult};
+
 #[derive(PartialEq)]
 enum PacketType {
     Sum,
@@ -129,19 +131,14 @@ fn eval_packet(p: &Packet) -> usize {
         return p.literal.unwrap();
     }
 
-    let mut value_arr = p
-        .subpackets
-        .as_ref()
-        .unwrap()
-        .iter()
-        .map(|sp| eval_packet(sp));
+    let mut value_arr = p.subpackets.as_ref().unwrap().iter().map(eval_packet);
 
     match p.packet_type {
         PacketType::Sum => value_arr.sum(),
         PacketType::Product => value_arr.product(),
         PacketType::Minimum => value_arr.min().unwrap(),
         PacketType::Maximum => value_arr.max().unwrap(),
-        PacketType::Literal => p.literal.unwrap() as usize,
+        PacketType::Literal => p.literal.unwrap(),
         PacketType::GreaterThan => (value_arr.next() > value_arr.next()) as usize,
         PacketType::LessThan => (value_arr.next() < value_arr.next()) as usize,
         PacketType::EqualTo => (value_arr.next() == value_arr.next()) as usize,
@@ -160,8 +157,21 @@ fn part2(line: &str) -> usize {
     eval_packet(&p)
 }
 
+fn parse_input(lines: &[String]) -> ParseResult<&str> {
+    let line = parse::first_line(lines)?;
+    match line.find(|c: char| !c.is_ascii_hexdigit()) {
+        Some(column) => Err(ParseError::new(column, "expected a hexadecimal digit")),
+        None => Ok(line),
+    }
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    solve_parts!(input, part)
+}
+
 pub fn run(lines: &[String]) {
-    let input = lines[0].as_str();
+    let input = parse_input(lines).unwrap();
     run_parts!(input);
 }
 
@@ -0,0 +1,190 @@
+use crate::parse::{self, ParseError, ParseResult};
+
+#[derive(Debug, PartialEq, Eq)]
+struct Target {
+    x: (isize, isize),
+    y: (isize, isize),
+}
+
+/// An inclusive range of steps, open-ended if `last` is `None`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Steps {
+    first: usize,
+    last: Option<usize>,
+}
+
+impl Steps {
+    fn overlaps(&self, other: &Steps) -> bool {
+        self.last.is_none_or(|last| other.first <= last)
+            && other.last.is_none_or(|last| self.first <= last)
+    }
+}
+
+fn triangle(n: isize) -> isize {
+    n * (n + 1) / 2
+}
+
+/// Steps at which a probe launched with horizontal velocity `vx` is inside the target.
+fn x_steps(vx: isize, target: &Target) -> Option<Steps> {
+    let (mut x, mut v, mut t) = (0, vx, 0);
+    while x < target.x.0 {
+        if v == 0 {
+            return None;
+        }
+        x += v;
+        v -= 1;
+        t += 1;
+    }
+    if x > target.x.1 {
+        return None;
+    }
+
+    let first = t;
+    while v > 0 && x + v <= target.x.1 {
+        x += v;
+        v -= 1;
+        t += 1;
+    }
+    // Once the probe stops inside the target, it stays there forever
+    let last = if v == 0 { None } else { Some(t) };
+    Some(Steps { first, last })
+}
+
+/// Steps at which a probe launched with vertical velocity `vy` is inside the target.
+fn y_steps(vy: isize, target: &Target) -> Option<Steps> {
+    let (mut y, mut v, mut t) = (0, vy, 0);
+    while y > target.y.1 {
+        y += v;
+        v -= 1;
+        t += 1;
+    }
+    if y < target.y.0 {
+        return None;
+    }
+
+    let first = t;
+    while y + v >= target.y.0 {
+        y += v;
+        v -= 1;
+        t += 1;
+    }
+    Some(Steps {
+        first,
+        last: Some(t),
+    })
+}
+
+/// All initial velocities that land in the target.
+///
+/// Horizontally, the probe must at least reach the near edge before drag
+/// stops it, and must not overshoot the far edge on the first step. Vertically,
+/// it must not overshoot the bottom edge on the first step, and a probe thrown
+/// upwards with `vy` comes back down through `y = 0` with velocity `-vy - 1`,
+/// so it must not overshoot the bottom edge on the step after that either.
+fn hitting_velocities(target: &Target) -> Vec<(isize, isize)> {
+    let vx_min = (0..).find(|&v| triangle(v) >= target.x.0).unwrap();
+    let vx_max = target.x.1;
+    let vy_min = target.y.0;
+    let vy_max = -target.y.0 - 1;
+
+    let xs: Vec<(isize, Steps)> = (vx_min..=vx_max)
+        .filter_map(|vx| x_steps(vx, target).map(|s| (vx, s)))
+        .collect();
+
+    let mut hits = Vec::new();
+    for vy in vy_min..=vy_max {
+        if let Some(ys) = y_steps(vy, target) {
+            for (vx, xs) in &xs {
+                if xs.overlaps(&ys) {
+                    hits.push((*vx, vy));
+                }
+            }
+        }
+    }
+    hits
+}
+
+fn part1(target: &Target) -> isize {
+    let vy = hitting_velocities(target)
+        .iter()
+        .map(|&(_, vy)| vy)
+        .max()
+        .unwrap();
+    triangle(vy.max(0))
+}
+
+fn part2(target: &Target) -> usize {
+    hitting_velocities(target).len()
+}
+
+fn parse_input(line: &str) -> ParseResult<Target> {
+    let prefix = "target area: ";
+    let area = parse::tag(line, prefix)?;
+    let (x, y) = parse::pair(
+        area,
+        ", ",
+        |s| parse::key_value(s, parse::range),
+        |s| parse::key_value(s, parse::range),
+    )
+    .map_err(|e| e.shifted(prefix.len()))?;
+
+    match (x, y) {
+        (("x", x), ("y", y)) if x.0 > 0 && y.1 < 0 => Ok(Target { x, y }),
+        (("x", _), ("y", _)) => Err(ParseError::new(
+            prefix.len(),
+            "target must be to the right of and below the launcher",
+        )),
+        _ => Err(ParseError::new(prefix.len(), "expected x=..., y=...")),
+    }
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let target = parse_input(parse::first_line(lines)?)?;
+    solve_parts!(target, part)
+}
+
+pub fn run(lines: &[String]) {
+    let target = parse_input(&lines[0]).unwrap();
+    run_parts!(target);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_test_input() -> String {
+        "target area: x=20..30, y=-10..-5".to_string()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse_input(&get_test_input()),
+            Ok(Target {
+                x: (20, 30),
+                y: (-10, -5)
+            })
+        );
+        assert_eq!(
+            parse_input("target area: x=20..30, y=-10..x")
+                .unwrap_err()
+                .column,
+            30
+        );
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(&parse_input(&get_test_input()).unwrap()), 45);
+    }
+
+    #[test]
+    fn test_part2() {
+        let target = parse_input(&get_test_input()).unwrap();
+        assert_eq!(part2(&target), 112);
+        let hits = hitting_velocities(&target);
+        assert!(hits.contains(&(6, 9)));
+        assert!(hits.contains(&(30, -10)));
+        assert!(!hits.contains(&(17, -4)));
+    }
+}
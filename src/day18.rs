@@ -0,0 +1,293 @@
+use crate::parse::{self, ParseError, ParseResult};
+use std::fmt::Display;
+use std::ops::Add;
+use std::str::FromStr;
+
+/// Puzzle numbers nest at most four pairs deep (five before an explode); the
+/// parser recurses per pair, so deeper input is rejected instead of
+/// overflowing the stack.
+const MAX_DEPTH: usize = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Snailfish {
+    Regular(u32),
+    Pair(Box<Snailfish>, Box<Snailfish>),
+}
+
+impl Snailfish {
+    fn pair(left: Snailfish, right: Snailfish) -> Self {
+        Snailfish::Pair(Box::new(left), Box::new(right))
+    }
+
+    fn magnitude(&self) -> u32 {
+        match self {
+            Snailfish::Regular(n) => *n,
+            Snailfish::Pair(l, r) => 3 * l.magnitude() + 2 * r.magnitude(),
+        }
+    }
+
+    fn add_leftmost(&mut self, value: u32) {
+        match self {
+            Snailfish::Regular(n) => *n += value,
+            Snailfish::Pair(l, _) => l.add_leftmost(value),
+        }
+    }
+
+    fn add_rightmost(&mut self, value: u32) {
+        match self {
+            Snailfish::Regular(n) => *n += value,
+            Snailfish::Pair(_, r) => r.add_rightmost(value),
+        }
+    }
+
+    /// Explodes the leftmost pair nested inside four pairs, returning the
+    /// values that still have to be added to the left and right neighbours.
+    fn explode(&mut self, depth: usize) -> Option<(Option<u32>, Option<u32>)> {
+        let (l, r) = match self {
+            Snailfish::Regular(_) => return None,
+            Snailfish::Pair(l, r) => (l, r),
+        };
+
+        if depth == 4 {
+            if let (Snailfish::Regular(a), Snailfish::Regular(b)) = (l.as_ref(), r.as_ref()) {
+                let carry = (Some(*a), Some(*b));
+                *self = Snailfish::Regular(0);
+                return Some(carry);
+            }
+        }
+
+        if let Some((left, right)) = l.explode(depth + 1) {
+            if let Some(value) = right {
+                r.add_leftmost(value);
+            }
+            return Some((left, None));
+        }
+
+        if let Some((left, right)) = r.explode(depth + 1) {
+            if let Some(value) = left {
+                l.add_rightmost(value);
+            }
+            return Some((None, right));
+        }
+
+        None
+    }
+
+    /// Splits the leftmost regular number of 10 or more.
+    fn split(&mut self) -> bool {
+        match self {
+            Snailfish::Regular(n) if *n >= 10 => {
+                *self =
+                    Snailfish::pair(Snailfish::Regular(*n / 2), Snailfish::Regular(*n - *n / 2));
+                true
+            }
+            Snailfish::Regular(_) => false,
+            Snailfish::Pair(l, r) => l.split() || r.split(),
+        }
+    }
+
+    fn reduce(&mut self) {
+        while self.explode(0).is_some() || self.split() {}
+    }
+}
+
+impl Add for Snailfish {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut sum = Snailfish::pair(self, rhs);
+        sum.reduce();
+        sum
+    }
+}
+
+impl Display for Snailfish {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Snailfish::Regular(n) => write!(f, "{}", n),
+            Snailfish::Pair(l, r) => write!(f, "[{},{}]", l, r),
+        }
+    }
+}
+
+fn parse_element(s: &str, pos: &mut usize, depth: usize) -> ParseResult<Snailfish> {
+    let expect = |pos: &mut usize, c: char| {
+        if s[*pos..].starts_with(c) {
+            *pos += 1;
+            Ok(())
+        } else {
+            Err(ParseError::new(*pos, format!("expected {:?}", c)))
+        }
+    };
+
+    if s[*pos..].starts_with('[') {
+        if depth == MAX_DEPTH {
+            return Err(ParseError::new(
+                *pos,
+                format!("pairs nested more than {} deep", MAX_DEPTH),
+            ));
+        }
+        *pos += 1;
+        let left = parse_element(s, pos, depth + 1)?;
+        expect(pos, ',')?;
+        let right = parse_element(s, pos, depth + 1)?;
+        expect(pos, ']')?;
+        Ok(Snailfish::pair(left, right))
+    } else {
+        let digits = s[*pos..].len()
+            - s[*pos..]
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .len();
+        if digits == 0 {
+            return Err(ParseError::new(*pos, "expected '[' or a number"));
+        }
+        let n = parse::number(&s[*pos..*pos + digits]).map_err(|e| e.shifted(*pos))?;
+        *pos += digits;
+        Ok(Snailfish::Regular(n))
+    }
+}
+
+impl FromStr for Snailfish {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut pos = 0;
+        let number = parse_element(s, &mut pos, 0)?;
+        if pos != s.len() {
+            return Err(ParseError::new(pos, "trailing characters"));
+        }
+        Ok(number)
+    }
+}
+
+fn sum(numbers: &[Snailfish]) -> Snailfish {
+    numbers.iter().cloned().reduce(|acc, n| acc + n).unwrap()
+}
+
+fn part1(numbers: &[Snailfish]) -> u32 {
+    sum(numbers).magnitude()
+}
+
+fn part2(numbers: &[Snailfish]) -> u32 {
+    let mut best = 0;
+    for (i, a) in numbers.iter().enumerate() {
+        for (j, b) in numbers.iter().enumerate() {
+            if i != j {
+                best = best.max((a.clone() + b.clone()).magnitude());
+            }
+        }
+    }
+    best
+}
+
+fn parse_input(lines: &[String]) -> ParseResult<Vec<Snailfish>> {
+    parse::lines(lines, Snailfish::from_str)
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    solve_parts!(input, part)
+}
+
+pub fn run(lines: &[String]) {
+    let input = parse_input(lines).unwrap();
+    run_parts!(input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_test_input() -> Vec<String> {
+        vec![
+            "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]".to_string(),
+            "[[[5,[2,8]],4],[5,[[9,9],0]]]".to_string(),
+            "[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]".to_string(),
+            "[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]".to_string(),
+            "[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]".to_string(),
+            "[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]".to_string(),
+            "[[[[5,4],[7,7]],8],[[8,3],8]]".to_string(),
+            "[[9,3],[[9,9],[6,[4,9]]]]".to_string(),
+            "[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]".to_string(),
+            "[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]".to_string(),
+        ]
+    }
+
+    fn num(s: &str) -> Snailfish {
+        Snailfish::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        for line in get_test_input() {
+            assert_eq!(num(&line).to_string(), line);
+        }
+        assert_eq!(Snailfish::from_str("[1,2").unwrap_err().column, 4);
+        assert_eq!(Snailfish::from_str("[1;2]").unwrap_err().column, 2);
+        assert_eq!(Snailfish::from_str("[1,2]]").unwrap_err().column, 5);
+        assert!(Snailfish::from_str("[[[[[[[[1,2],3],4],5],6],7],8],9]").is_ok());
+        assert_eq!(
+            Snailfish::from_str("[[[[[[[[[1,2],3],4],5],6],7],8],9],0]")
+                .unwrap_err()
+                .column,
+            8
+        );
+        let deep = "[".repeat(200_000);
+        assert_eq!(Snailfish::from_str(&deep).unwrap_err().column, MAX_DEPTH);
+    }
+
+    #[test]
+    fn test_explode() {
+        let cases = [
+            ("[[[[[9,8],1],2],3],4]", "[[[[0,9],2],3],4]"),
+            ("[7,[6,[5,[4,[3,2]]]]]", "[7,[6,[5,[7,0]]]]"),
+            ("[[6,[5,[4,[3,2]]]],1]", "[[6,[5,[7,0]]],3]"),
+            (
+                "[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]",
+                "[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]",
+            ),
+        ];
+        for (before, after) in cases {
+            let mut n = num(before);
+            assert!(n.explode(0).is_some());
+            assert_eq!(n.to_string(), after);
+        }
+    }
+
+    #[test]
+    fn test_add() {
+        assert_eq!(
+            (num("[[[[4,3],4],4],[7,[[8,4],9]]]") + num("[1,1]")).to_string(),
+            "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]"
+        );
+        let numbers: Vec<Snailfish> = ["[1,1]", "[2,2]", "[3,3]", "[4,4]", "[5,5]", "[6,6]"]
+            .iter()
+            .map(|s| num(s))
+            .collect();
+        assert_eq!(sum(&numbers).to_string(), "[[[[5,0],[7,4]],[5,5]],[6,6]]");
+    }
+
+    #[test]
+    fn test_magnitude() {
+        assert_eq!(num("[[1,2],[[3,4],5]]").magnitude(), 143);
+        assert_eq!(
+            num("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]").magnitude(),
+            3488
+        );
+    }
+
+    #[test]
+    fn test_part1() {
+        let numbers = parse_input(&get_test_input()).unwrap();
+        assert_eq!(
+            sum(&numbers).to_string(),
+            "[[[[6,6],[7,6]],[[7,7],[7,0]]],[[[7,7],[7,7]],[[7,8],[9,9]]]]"
+        );
+        assert_eq!(part1(&numbers), 4140);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(&parse_input(&get_test_input()).unwrap()), 3993);
+    }
+}
@@ -0,0 +1,450 @@
+use crate::parse::{self, ParseError, ParseResult};
+use crate::trace::Level;
+use std::collections::{HashMap, HashSet};
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+
+/// The number of shared beacons needed to consider two scanners aligned.
+const OVERLAP: usize = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Point {
+    x: isize,
+    y: isize,
+    z: isize,
+}
+
+impl Point {
+    fn manhattan(self) -> isize {
+        self.x.abs() + self.y.abs() + self.z.abs()
+    }
+
+    fn squared_norm(self) -> isize {
+        self.x * self.x + self.y * self.y + self.z * self.z
+    }
+
+    fn coord(self, axis: usize) -> isize {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            _ => self.z,
+        }
+    }
+}
+
+impl FromStr for Point {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, yz) = parse::split_pair(s, ",")?;
+        let (y, z) = parse::pair(yz, ",", parse::number, parse::number)
+            .map_err(|e| e.shifted(x.len() + 1))?;
+        Ok(Point {
+            x: parse::number(x)?,
+            y,
+            z,
+        })
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Point {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Point {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+/// An axis-aligned rotation: output axis `i` is input axis `axes[i]` times `signs[i]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Rotation {
+    axes: [usize; 3],
+    signs: [isize; 3],
+}
+
+impl Rotation {
+    fn apply(&self, p: Point) -> Point {
+        Point {
+            x: p.coord(self.axes[0]) * self.signs[0],
+            y: p.coord(self.axes[1]) * self.signs[1],
+            z: p.coord(self.axes[2]) * self.signs[2],
+        }
+    }
+
+    /// The 24 proper rotations: every axis permutation and sign combination
+    /// whose determinant is +1.
+    fn all() -> Vec<Rotation> {
+        let permutations = [
+            ([0, 1, 2], 1),
+            ([1, 2, 0], 1),
+            ([2, 0, 1], 1),
+            ([0, 2, 1], -1),
+            ([2, 1, 0], -1),
+            ([1, 0, 2], -1),
+        ];
+        let mut rotations = Vec::new();
+        for (axes, parity) in permutations {
+            for bits in 0..8 {
+                let signs = [
+                    if bits & 1 == 0 { 1 } else { -1 },
+                    if bits & 2 == 0 { 1 } else { -1 },
+                    if bits & 4 == 0 { 1 } else { -1 },
+                ];
+                if signs.iter().product::<isize>() == parity {
+                    rotations.push(Rotation { axes, signs });
+                }
+            }
+        }
+        rotations
+    }
+}
+
+#[derive(Debug)]
+struct Scanner {
+    beacons: Vec<Point>,
+    /// Squared distances between every pair of beacons, invariant under
+    /// rotation and translation.
+    fingerprint: HashMap<isize, usize>,
+}
+
+impl Scanner {
+    fn new(beacons: Vec<Point>) -> Self {
+        let mut fingerprint = HashMap::new();
+        for (i, a) in beacons.iter().enumerate() {
+            for b in &beacons[i + 1..] {
+                *fingerprint.entry((*a - *b).squared_norm()).or_default() += 1;
+            }
+        }
+        Self {
+            beacons,
+            fingerprint,
+        }
+    }
+
+    /// Whether the scanners share enough beacon distances to possibly overlap.
+    fn may_overlap(&self, other: &Scanner) -> bool {
+        let shared: usize = self
+            .fingerprint
+            .iter()
+            .map(|(d, n)| other.fingerprint.get(d).map_or(0, |m| *n.min(m)))
+            .sum();
+        shared >= OVERLAP * (OVERLAP - 1) / 2
+    }
+
+    /// Finds the rotation and translation that map `other`'s beacons onto
+    /// at least `OVERLAP` of this scanner's beacons.
+    fn align(&self, other: &Scanner, rotations: &[Rotation]) -> Option<(Rotation, Point)> {
+        for rotation in rotations {
+            let rotated: Vec<Point> = other.beacons.iter().map(|&b| rotation.apply(b)).collect();
+            let mut offsets: HashMap<Point, usize> = HashMap::new();
+            for a in &self.beacons {
+                for b in &rotated {
+                    let count = offsets.entry(*a - *b).or_default();
+                    *count += 1;
+                    if *count >= OVERLAP {
+                        return Some((*rotation, *a - *b));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Aligns every scanner to scanner 0, returning the absolute scanner
+/// positions and the set of all beacons.
+fn locate(scanners: &[Scanner]) -> (Vec<Point>, HashSet<Point>) {
+    let rotations = Rotation::all();
+    let origin = Point { x: 0, y: 0, z: 0 };
+
+    // Beacons of aligned scanners, in scanner 0's coordinate system
+    let mut aligned: Vec<Option<Scanner>> = scanners.iter().map(|_| None).collect();
+    let mut positions = vec![origin; scanners.len()];
+    aligned[0] = Some(Scanner::new(scanners[0].beacons.clone()));
+
+    let mut frontier = vec![0];
+    let mut tried = HashSet::new();
+    while let Some(i) = frontier.pop() {
+        for j in 0..scanners.len() {
+            if aligned[j].is_some() || !tried.insert((i, j)) {
+                continue;
+            }
+            let reference = aligned[i].as_ref().unwrap();
+            if !reference.may_overlap(&scanners[j]) {
+                continue;
+            }
+            if let Some((rotation, offset)) = reference.align(&scanners[j], &rotations) {
+                event!(
+                    Level::Debug,
+                    "aligned",
+                    scanner = j,
+                    to = i,
+                    position = offset
+                );
+                let beacons = scanners[j]
+                    .beacons
+                    .iter()
+                    .map(|&b| rotation.apply(b) + offset)
+                    .collect();
+                aligned[j] = Some(Scanner::new(beacons));
+                positions[j] = offset;
+                frontier.push(j);
+            }
+        }
+    }
+
+    assert!(
+        aligned.iter().all(|s| s.is_some()),
+        "some scanners could not be aligned"
+    );
+
+    let beacons = aligned
+        .into_iter()
+        .flatten()
+        .flat_map(|s| s.beacons)
+        .collect();
+    (positions, beacons)
+}
+
+fn part1(scanners: &[Scanner]) -> usize {
+    locate(scanners).1.len()
+}
+
+fn part2(scanners: &[Scanner]) -> isize {
+    let (positions, _) = locate(scanners);
+    let mut best = 0;
+    for a in &positions {
+        for b in &positions {
+            best = best.max((*a - *b).manhattan());
+        }
+    }
+    best
+}
+
+fn parse_input(lines: &[String]) -> ParseResult<Vec<Scanner>> {
+    parse::sections(lines)
+        .iter()
+        .map(|section| {
+            let header = section.first()?;
+            if !(header.starts_with("--- scanner ") && header.ends_with(" ---")) {
+                return Err(ParseError::new(0, "expected scanner header").on_line(section.start));
+            }
+            let beacons = parse::lines(&section.lines[1..], Point::from_str)
+                .map_err(|e| e.on_line(section.start + 1))?;
+            Ok(Scanner::new(beacons))
+        })
+        .collect()
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    solve_parts!(input, part)
+}
+
+pub fn run(lines: &[String]) {
+    let input = parse_input(lines).unwrap();
+    run_parts!(input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_test_input() -> Vec<String> {
+        vec![
+            "--- scanner 0 ---".to_string(),
+            "404,-588,-901".to_string(),
+            "528,-643,409".to_string(),
+            "-838,591,734".to_string(),
+            "390,-675,-793".to_string(),
+            "-537,-823,-458".to_string(),
+            "-485,-357,347".to_string(),
+            "-345,-311,381".to_string(),
+            "-661,-816,-575".to_string(),
+            "-876,649,763".to_string(),
+            "-618,-824,-621".to_string(),
+            "553,345,-567".to_string(),
+            "474,580,667".to_string(),
+            "-447,-329,318".to_string(),
+            "-584,868,-557".to_string(),
+            "544,-627,-890".to_string(),
+            "564,392,-477".to_string(),
+            "455,729,728".to_string(),
+            "-892,524,684".to_string(),
+            "-689,845,-530".to_string(),
+            "423,-701,434".to_string(),
+            "7,-33,-71".to_string(),
+            "630,319,-379".to_string(),
+            "443,580,662".to_string(),
+            "-789,900,-551".to_string(),
+            "459,-707,401".to_string(),
+            "".to_string(),
+            "--- scanner 1 ---".to_string(),
+            "686,422,578".to_string(),
+            "605,423,415".to_string(),
+            "515,917,-361".to_string(),
+            "-336,658,858".to_string(),
+            "95,138,22".to_string(),
+            "-476,619,847".to_string(),
+            "-340,-569,-846".to_string(),
+            "567,-361,727".to_string(),
+            "-460,603,-452".to_string(),
+            "669,-402,600".to_string(),
+            "729,430,532".to_string(),
+            "-500,-761,534".to_string(),
+            "-322,571,750".to_string(),
+            "-466,-666,-811".to_string(),
+            "-429,-592,574".to_string(),
+            "-355,545,-477".to_string(),
+            "703,-491,-529".to_string(),
+            "-328,-685,520".to_string(),
+            "413,935,-424".to_string(),
+            "-391,539,-444".to_string(),
+            "586,-435,557".to_string(),
+            "-364,-763,-893".to_string(),
+            "807,-499,-711".to_string(),
+            "755,-354,-619".to_string(),
+            "553,889,-390".to_string(),
+            "".to_string(),
+            "--- scanner 2 ---".to_string(),
+            "649,640,665".to_string(),
+            "682,-795,504".to_string(),
+            "-784,533,-524".to_string(),
+            "-644,584,-595".to_string(),
+            "-588,-843,648".to_string(),
+            "-30,6,44".to_string(),
+            "-674,560,763".to_string(),
+            "500,723,-460".to_string(),
+            "609,671,-379".to_string(),
+            "-555,-800,653".to_string(),
+            "-675,-892,-343".to_string(),
+            "697,-426,-610".to_string(),
+            "578,704,681".to_string(),
+            "493,664,-388".to_string(),
+            "-671,-858,530".to_string(),
+            "-667,343,800".to_string(),
+            "571,-461,-707".to_string(),
+            "-138,-166,112".to_string(),
+            "-889,563,-600".to_string(),
+            "646,-828,498".to_string(),
+            "640,759,510".to_string(),
+            "-630,509,768".to_string(),
+            "-681,-892,-333".to_string(),
+            "673,-379,-804".to_string(),
+            "-742,-814,-386".to_string(),
+            "577,-820,562".to_string(),
+            "".to_string(),
+            "--- scanner 3 ---".to_string(),
+            "-589,542,597".to_string(),
+            "605,-692,669".to_string(),
+            "-500,565,-823".to_string(),
+            "-660,373,557".to_string(),
+            "-458,-679,-417".to_string(),
+            "-488,449,543".to_string(),
+            "-626,468,-788".to_string(),
+            "338,-750,-386".to_string(),
+            "528,-832,-391".to_string(),
+            "562,-778,733".to_string(),
+            "-938,-730,414".to_string(),
+            "543,643,-506".to_string(),
+            "-524,371,-870".to_string(),
+            "407,773,750".to_string(),
+            "-104,29,83".to_string(),
+            "378,-903,-323".to_string(),
+            "-778,-728,485".to_string(),
+            "426,699,580".to_string(),
+            "-438,-605,-362".to_string(),
+            "-469,-447,-387".to_string(),
+            "509,732,623".to_string(),
+            "647,635,-688".to_string(),
+            "-868,-804,481".to_string(),
+            "614,-800,639".to_string(),
+            "595,780,-596".to_string(),
+            "".to_string(),
+            "--- scanner 4 ---".to_string(),
+            "727,592,562".to_string(),
+            "-293,-554,779".to_string(),
+            "441,611,-461".to_string(),
+            "-714,465,-776".to_string(),
+            "-743,427,-804".to_string(),
+            "-660,-479,-426".to_string(),
+            "832,-632,460".to_string(),
+            "927,-485,-438".to_string(),
+            "408,393,-506".to_string(),
+            "466,436,-512".to_string(),
+            "110,16,151".to_string(),
+            "-258,-428,682".to_string(),
+            "-393,719,612".to_string(),
+            "-211,-452,876".to_string(),
+            "808,-476,-593".to_string(),
+            "-575,615,604".to_string(),
+            "-485,667,467".to_string(),
+            "-680,325,-822".to_string(),
+            "-627,-443,-432".to_string(),
+            "872,-547,-609".to_string(),
+            "833,512,582".to_string(),
+            "807,604,487".to_string(),
+            "839,-516,451".to_string(),
+            "891,-625,532".to_string(),
+            "-652,-548,-490".to_string(),
+            "30,-46,-14".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_rotations() {
+        let rotations = Rotation::all();
+        assert_eq!(rotations.len(), 24);
+        let p = Point { x: 1, y: 2, z: 3 };
+        let images: HashSet<Point> = rotations.iter().map(|r| r.apply(p)).collect();
+        assert_eq!(images.len(), 24);
+    }
+
+    #[test]
+    fn test_parse() {
+        let lines: Vec<String> = vec!["--- scanner 0 ---", "404,-588,-901", "528,-643,x"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let err = parse_input(&lines).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 9));
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(&parse_input(&get_test_input()).unwrap()), 79);
+    }
+
+    #[test]
+    fn test_part2() {
+        let scanners = parse_input(&get_test_input()).unwrap();
+        let point = |x, y, z| Point { x, y, z };
+        assert_eq!(
+            locate(&scanners).0,
+            [
+                point(0, 0, 0),
+                point(68, -1246, -43),
+                point(1105, -1205, 1229),
+                point(-92, -2380, -20),
+                point(-20, -1133, 1061),
+            ]
+        );
+        assert_eq!(part2(&scanners), 3621);
+    }
+}
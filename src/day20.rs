@@ -0,0 +1,185 @@
+use crate::parse::{self, ParseError, ParseResult};
+use ndarray::{s, Array2};
+
+struct Image {
+    pixels: Array2<bool>,
+    /// The state of every pixel outside `pixels`, out to infinity.
+    background: bool,
+}
+
+impl Image {
+    fn lit(&self) -> usize {
+        assert!(!self.background, "infinitely many pixels are lit");
+        self.pixels.iter().filter(|&&p| p).count()
+    }
+
+    fn enhance(&self, algorithm: &[bool]) -> Image {
+        let (rows, cols) = self.pixels.dim();
+        let mut padded = Array2::from_elem((rows + 4, cols + 4), self.background);
+        padded
+            .slice_mut(s![2..rows + 2, 2..cols + 2])
+            .assign(&self.pixels);
+
+        let pixels = Array2::from_shape_fn((rows + 2, cols + 2), |(i, j)| {
+            let index = padded
+                .slice(s![i..i + 3, j..j + 3])
+                .iter()
+                .fold(0, |acc, &p| acc << 1 | p as usize);
+            algorithm[index]
+        });
+
+        // Every background pixel sees nine background pixels
+        let background = algorithm[if self.background { 511 } else { 0 }];
+        Image { pixels, background }
+    }
+}
+
+fn enhance_times(input: &(Vec<bool>, Array2<bool>), steps: usize) -> usize {
+    let (algorithm, pixels) = input;
+    let mut image = Image {
+        pixels: pixels.clone(),
+        background: false,
+    };
+    for _ in 0..steps {
+        image = image.enhance(algorithm);
+    }
+    image.lit()
+}
+
+fn part1(input: &(Vec<bool>, Array2<bool>)) -> usize {
+    enhance_times(input, 2)
+}
+
+fn part2(input: &(Vec<bool>, Array2<bool>)) -> usize {
+    enhance_times(input, 50)
+}
+
+fn pixel(c: char, column: usize) -> ParseResult<bool> {
+    match c {
+        '#' => Ok(true),
+        '.' => Ok(false),
+        _ => Err(ParseError::new(
+            column,
+            format!("expected '#' or '.', found {:?}", c),
+        )),
+    }
+}
+
+fn pixels(line: &str) -> ParseResult<Vec<bool>> {
+    line.chars().enumerate().map(|(j, c)| pixel(c, j)).collect()
+}
+
+fn parse_input(lines: &[String]) -> ParseResult<(Vec<bool>, Array2<bool>)> {
+    let sections = parse::n_sections(lines, 2)?;
+
+    let algorithm = sections[0].parse(pixels)?.concat();
+    if algorithm.len() != 512 {
+        return Err(ParseError::new(
+            0,
+            format!("expected 512 algorithm entries, found {}", algorithm.len()),
+        ));
+    }
+
+    let rows = parse::grid(sections[1].lines, pixel).map_err(|e| e.on_line(sections[1].start))?;
+    let width = rows[0].len();
+    let image = Array2::from_shape_vec((rows.len(), width), rows.concat()).unwrap();
+
+    Ok((algorithm, image))
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    solve_parts!(input, part)
+}
+
+pub fn run(lines: &[String]) {
+    let input = parse_input(lines).unwrap();
+    run_parts!(input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The image enhancement algorithm from the puzzle's example.
+    const EXAMPLE_ALGORITHM: &str = concat!(
+        "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#.",
+        ".#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..",
+        "#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....",
+        "#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####",
+        ".#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.",
+        "#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..",
+        "#.##.#....##..#.####....##...##..#...#......#.#.......#.......##",
+        "..####..#...#.#.#...##..#.#..###..#####........#..####......#..#",
+    );
+
+    /// An algorithm computing `f` of the 9-bit neighbourhood index.
+    fn algorithm(f: impl Fn(usize) -> bool) -> String {
+        (0..512).map(|i| if f(i) { '#' } else { '.' }).collect()
+    }
+
+    fn get_test_input(algorithm: String) -> Vec<String> {
+        vec![
+            algorithm,
+            "".to_string(),
+            "#..#.".to_string(),
+            "#....".to_string(),
+            "##..#".to_string(),
+            "..#..".to_string(),
+            "..###".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = parse_input(&get_test_input(EXAMPLE_ALGORITHM.to_string())).unwrap();
+        assert_eq!(part1(&input), 35);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = parse_input(&get_test_input(EXAMPLE_ALGORITHM.to_string())).unwrap();
+        assert_eq!(part2(&input), 3351);
+    }
+
+    #[test]
+    fn test_identity() {
+        let input = parse_input(&get_test_input(algorithm(|i| i & 0b10000 != 0))).unwrap();
+        assert_eq!(enhance_times(&input, 0), 10);
+        assert_eq!(part1(&input), 10);
+        assert_eq!(part2(&input), 10);
+    }
+
+    #[test]
+    fn test_dilation() {
+        let input = parse_input(&get_test_input(algorithm(|i| i != 0))).unwrap();
+        let mut single = input.clone();
+        single.1 = Array2::from_elem((1, 1), true);
+        assert_eq!(part1(&single), 25);
+        assert_eq!(part2(&single), 101 * 101);
+        assert_eq!(part1(&input), 75);
+    }
+
+    #[test]
+    fn test_flashing_background() {
+        // Inverts the centre pixel, so index 0 is lit and the background flashes
+        let input = parse_input(&get_test_input(algorithm(|i| i & 0b10000 == 0))).unwrap();
+        assert!(input.0[0]);
+        assert_eq!(part1(&input), 10);
+        assert_eq!(part2(&input), 10);
+
+        let image = Image {
+            pixels: input.1.clone(),
+            background: false,
+        };
+        assert!(image.enhance(&input.0).background);
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut lines = get_test_input(algorithm(|_| false));
+        lines[4] = "#.x..".to_string();
+        let err = parse_input(&lines).unwrap_err();
+        assert_eq!((err.line, err.column), (4, 2));
+    }
+}
@@ -0,0 +1,131 @@
+use crate::parse::{self, ParseError, ParseResult};
+use std::collections::HashMap;
+
+/// How many of the 27 universes split off by three rolls of the Dirac die
+/// produce each total.
+const ROLL_FREQUENCIES: [(usize, usize); 7] =
+    [(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)];
+
+fn advance(position: usize, steps: usize) -> usize {
+    (position + steps - 1) % 10 + 1
+}
+
+fn part1(start: &[usize; 2]) -> usize {
+    let mut positions = *start;
+    let mut scores = [0; 2];
+    let mut die = (1..=100).cycle();
+    let mut rolls = 0;
+
+    for turn in (0..2).cycle() {
+        let steps: usize = die.by_ref().take(3).sum();
+        rolls += 3;
+        positions[turn] = advance(positions[turn], steps);
+        scores[turn] += positions[turn];
+        if scores[turn] >= 1000 {
+            return scores[1 - turn] * rolls;
+        }
+    }
+    unreachable!()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct Game {
+    positions: [usize; 2],
+    scores: [usize; 2],
+    turn: usize,
+}
+
+/// Counts the universes in which each player wins from the given game state.
+fn count_wins(game: Game, memo: &mut HashMap<Game, [usize; 2]>) -> [usize; 2] {
+    if let Some(wins) = memo.get(&game) {
+        return *wins;
+    }
+
+    let mut wins = [0; 2];
+    for (steps, universes) in ROLL_FREQUENCIES {
+        let mut next = game;
+        let player = game.turn;
+        next.positions[player] = advance(game.positions[player], steps);
+        next.scores[player] += next.positions[player];
+
+        if next.scores[player] >= 21 {
+            wins[player] += universes;
+        } else {
+            next.turn = 1 - player;
+            let sub_wins = count_wins(next, memo);
+            wins[0] += universes * sub_wins[0];
+            wins[1] += universes * sub_wins[1];
+        }
+    }
+
+    memo.insert(game, wins);
+    wins
+}
+
+fn part2(start: &[usize; 2]) -> usize {
+    let game = Game {
+        positions: *start,
+        scores: [0; 2],
+        turn: 0,
+    };
+    let wins = count_wins(game, &mut HashMap::new());
+    wins[0].max(wins[1])
+}
+
+fn parse_input(lines: &[String]) -> ParseResult<[usize; 2]> {
+    let positions = parse::lines(lines, |line| {
+        let (_, position) = parse::split_pair(line, "starting position: ")?;
+        let column = line.len() - position.len();
+        match parse::number(position).map_err(|e| e.shifted(column))? {
+            p @ 1..=10 => Ok(p),
+            _ => Err(ParseError::new(column, "position must be between 1 and 10")),
+        }
+    })?;
+
+    match positions.as_slice() {
+        [p1, p2] => Ok([*p1, *p2]),
+        _ => Err(ParseError::new(0, "expected two players").on_line(positions.len())),
+    }
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    solve_parts!(input, part)
+}
+
+pub fn run(lines: &[String]) {
+    let input = parse_input(lines).unwrap();
+    run_parts!(input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_test_input() -> Vec<String> {
+        vec![
+            "Player 1 starting position: 4".to_string(),
+            "Player 2 starting position: 8".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_input(&get_test_input()), Ok([4, 8]));
+        let err = parse_input(&["Player 1 starting position: 11".to_string()]).unwrap_err();
+        assert_eq!((err.line, err.column), (0, 28));
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(&parse_input(&get_test_input()).unwrap()), 739785);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(
+            part2(&parse_input(&get_test_input()).unwrap()),
+            444356092776315
+        );
+    }
+}
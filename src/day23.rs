@@ -0,0 +1,291 @@
+use crate::day15::dijkstra;
+use crate::parse::{ParseError, ParseResult};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+const HALLWAY: usize = 11;
+const ROOMS: usize = 4;
+const MAX_DEPTH: usize = 4;
+
+/// Hallway cells an amphipod may stop in, i.e. every cell not outside a room.
+const STOPS: [usize; 7] = [0, 1, 3, 5, 7, 9, 10];
+
+/// The amphipods inserted into each room when the diagram is unfolded,
+/// from the rows `#D#C#B#A#` and `#D#B#A#C#`.
+const FOLDED: [[u8; 2]; ROOMS] = [[4, 4], [3, 2], [2, 1], [1, 3]];
+
+/// The hallway cell outside a room.
+fn door(room: usize) -> usize {
+    2 + 2 * room
+}
+
+fn energy(amphipod: u8) -> usize {
+    10usize.pow(amphipod as u32 - 1)
+}
+
+/// A burrow, with every cell encoded as 0 for empty or 1-4 for amphipods
+/// A-D. Cells `0..HALLWAY` are the hallway from left to right, and room `r`
+/// occupies `HALLWAY + r * MAX_DEPTH ..` from the top down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Burrow {
+    cells: [u8; HALLWAY + ROOMS * MAX_DEPTH],
+    depth: usize,
+}
+
+impl Burrow {
+    fn room(&self, room: usize) -> &[u8] {
+        let start = HALLWAY + room * MAX_DEPTH;
+        &self.cells[start..start + self.depth]
+    }
+
+    /// Whether every amphipod in the room belongs there.
+    fn room_settled(&self, room: usize) -> bool {
+        self.room(room)
+            .iter()
+            .all(|&a| a == 0 || a as usize == room + 1)
+    }
+
+    fn is_organised(&self) -> bool {
+        (0..ROOMS).all(|r| self.room(r).iter().all(|&a| a as usize == r + 1))
+    }
+
+    /// Whether the hallway between `from` and `to` is empty, not counting `from`.
+    fn hallway_clear(&self, from: usize, to: usize) -> bool {
+        let range = if from < to {
+            from + 1..=to
+        } else {
+            to..=from - 1
+        };
+        self.cells[range].iter().all(|&a| a == 0)
+    }
+
+    fn moved(&self, from: usize, to: usize) -> Burrow {
+        let mut next = *self;
+        next.cells[to] = next.cells[from];
+        next.cells[from] = 0;
+        next
+    }
+
+    /// Every legal move from this burrow, with its energy cost. Amphipods
+    /// only leave a room for a hallway stop, and only enter their own room
+    /// once it holds no strangers, going as deep as they can.
+    fn moves(&self) -> Vec<(Burrow, usize)> {
+        let mut moves = Vec::new();
+
+        for from in 0..HALLWAY {
+            let amphipod = self.cells[from];
+            if amphipod == 0 {
+                continue;
+            }
+            let room = amphipod as usize - 1;
+            if !self.room_settled(room) || !self.hallway_clear(from, door(room)) {
+                continue;
+            }
+            let slot = self.room(room).iter().rposition(|&a| a == 0).unwrap();
+            let steps = from.abs_diff(door(room)) + slot + 1;
+            let to = HALLWAY + room * MAX_DEPTH + slot;
+            moves.push((self.moved(from, to), steps * energy(amphipod)));
+        }
+
+        for room in 0..ROOMS {
+            if self.room_settled(room) {
+                continue;
+            }
+            let slot = self.room(room).iter().position(|&a| a != 0).unwrap();
+            let from = HALLWAY + room * MAX_DEPTH + slot;
+            let amphipod = self.cells[from];
+            for to in STOPS {
+                if self.cells[to] == 0 && self.hallway_clear(door(room), to) {
+                    let steps = slot + 1 + door(room).abs_diff(to);
+                    moves.push((self.moved(from, to), steps * energy(amphipod)));
+                }
+            }
+        }
+
+        moves
+    }
+
+    /// Inserts the two folded rows between the first and last row of rooms.
+    fn unfold(&self) -> Burrow {
+        assert_eq!(self.depth, 2, "only a 2-deep burrow can be unfolded");
+        let mut unfolded = *self;
+        unfolded.depth = MAX_DEPTH;
+        for (room, folded) in FOLDED.iter().enumerate() {
+            let start = HALLWAY + room * MAX_DEPTH;
+            unfolded.cells[start + 1..start + 3].copy_from_slice(folded);
+            unfolded.cells[start + 3] = self.cells[start + 1];
+        }
+        unfolded
+    }
+}
+
+impl Display for Burrow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cell = |a: u8| match a {
+            0 => '.',
+            a => (b'A' + a - 1) as char,
+        };
+        writeln!(f, "#############")?;
+        let hallway: String = self.cells[..HALLWAY].iter().map(|&a| cell(a)).collect();
+        writeln!(f, "#{}#", hallway)?;
+        for slot in 0..self.depth {
+            let row: Vec<String> = (0..ROOMS)
+                .map(|r| cell(self.room(r)[slot]).to_string())
+                .collect();
+            let edge = if slot == 0 { "##" } else { "  " };
+            writeln!(f, "{}#{}#{}", edge, row.join("#"), edge.trim())?;
+        }
+        write!(f, "  #########")
+    }
+}
+
+fn organise(burrow: &Burrow) -> usize {
+    dijkstra(*burrow, Burrow::is_organised, Burrow::moves, HashMap::new())
+        .expect("burrow cannot be organised")
+}
+
+fn part1(burrow: &Burrow) -> usize {
+    organise(burrow)
+}
+
+/// Organises the unfolded burrow; a diagram that is already unfolded is
+/// organised as it is.
+fn part2(burrow: &Burrow) -> usize {
+    if burrow.depth == MAX_DEPTH {
+        organise(burrow)
+    } else {
+        organise(&burrow.unfold())
+    }
+}
+
+fn parse_input(lines: &[String]) -> ParseResult<Burrow> {
+    let expect = |i: usize, expected: &str| match lines.get(i) {
+        Some(line) if line.trim_end() == expected => Ok(()),
+        _ => Err(ParseError::new(0, format!("expected {:?}", expected)).on_line(i)),
+    };
+    expect(0, "#############")?;
+    expect(1, "#...........#")?;
+
+    let rows = &lines[2..];
+    let depth = rows
+        .iter()
+        .position(|line| line.trim() == "#########")
+        .ok_or_else(|| ParseError::new(0, "expected bottom wall").on_line(lines.len()))?;
+    if depth != 2 && depth != MAX_DEPTH {
+        return Err(ParseError::new(0, "expected 2 or 4 rows of rooms").on_line(2));
+    }
+
+    let mut burrow = Burrow {
+        cells: [0; HALLWAY + ROOMS * MAX_DEPTH],
+        depth,
+    };
+    for (slot, line) in rows[..depth].iter().enumerate() {
+        for room in 0..ROOMS {
+            let column = door(room) + 1;
+            burrow.cells[HALLWAY + room * MAX_DEPTH + slot] = match line.chars().nth(column) {
+                Some(c @ 'A'..='D') => c as u8 - b'A' + 1,
+                _ => {
+                    return Err(
+                        ParseError::new(column, "expected an amphipod A-D").on_line(2 + slot)
+                    )
+                }
+            };
+        }
+    }
+
+    for amphipod in 1..=ROOMS as u8 {
+        if burrow.cells.iter().filter(|&&a| a == amphipod).count() != depth {
+            return Err(
+                ParseError::new(0, format!("expected {} of each amphipod", depth)).on_line(2),
+            );
+        }
+    }
+
+    Ok(burrow)
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    solve_parts!(input, part)
+}
+
+pub fn run(lines: &[String]) {
+    let input = parse_input(lines).unwrap();
+    run_parts!(input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::split_lines;
+
+    fn get_test_input() -> Vec<String> {
+        vec![
+            "#############".to_string(),
+            "#...........#".to_string(),
+            "###B#C#B#D###".to_string(),
+            "  #A#D#C#A#".to_string(),
+            "  #########".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_parse() {
+        let burrow = parse_input(&get_test_input()).unwrap();
+        assert_eq!(burrow.depth, 2);
+        assert_eq!(burrow.room(0), [2, 1]);
+        assert_eq!(
+            burrow.unfold().to_string().lines().collect::<Vec<_>>(),
+            [
+                "#############",
+                "#...........#",
+                "###B#C#B#D###",
+                "  #D#C#B#A#",
+                "  #D#B#A#C#",
+                "  #A#D#C#A#",
+                "  #########",
+            ]
+        );
+
+        let mut lines = get_test_input();
+        lines[3] = "  #A#D#x#A#".to_string();
+        let err = parse_input(&lines).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 7));
+    }
+
+    #[test]
+    fn test_moves() {
+        let burrow = parse_input(&get_test_input()).unwrap();
+        // Every room holds a stranger, so every top amphipod can reach all stops
+        assert_eq!(burrow.moves().len(), 4 * STOPS.len());
+
+        let organised = Burrow {
+            cells: [
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 3, 3, 0, 0, 4, 4, 0, 0,
+            ],
+            depth: 2,
+        };
+        assert!(organised.is_organised());
+        assert!(organised.moves().is_empty());
+        assert_eq!(organise(&organised.moved(12, 0).moved(11, 1)), 3 + 3);
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(&parse_input(&get_test_input()).unwrap()), 12521);
+    }
+
+    #[test]
+    fn test_part2() {
+        let burrow = parse_input(&get_test_input()).unwrap();
+        assert_eq!(part2(&burrow), 44169);
+
+        let unfolded = split_lines(&burrow.unfold().to_string());
+        assert_eq!(solve(&unfolded, 1), Ok(Some("44169".to_string())));
+        assert_eq!(solve(&unfolded, 2), Ok(Some("44169".to_string())));
+
+        let mut lines = get_test_input();
+        lines.insert(3, "  #A#D#C#A#".to_string());
+        assert_eq!(parse_input(&lines).unwrap_err().line, 2);
+    }
+}
@@ -0,0 +1,366 @@
+use crate::parse::{self, ParseError, ParseResult};
+use crate::trace::Level;
+use std::str::FromStr;
+
+const Z: usize = 3;
+
+/// The 18 instructions MONAD runs for every digit, with the three
+/// parameters that vary between blocks as placeholders.
+const BLOCK: [&str; 18] = [
+    "inp w",
+    "mul x 0",
+    "add x z",
+    "mod x 26",
+    "div z {divisor}",
+    "add x {check}",
+    "eql x w",
+    "eql x 0",
+    "mul y 0",
+    "add y 25",
+    "mul y x",
+    "add y 1",
+    "mul z y",
+    "mul y 0",
+    "add y w",
+    "add y {offset}",
+    "mul y x",
+    "add z y",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operand {
+    Register(usize),
+    Number(i64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Instruction {
+    Inp(usize),
+    Add(usize, Operand),
+    Mul(usize, Operand),
+    Div(usize, Operand),
+    Mod(usize, Operand),
+    Eql(usize, Operand),
+}
+
+fn register(s: &str) -> ParseResult<usize> {
+    match s {
+        "w" => Ok(0),
+        "x" => Ok(1),
+        "y" => Ok(2),
+        "z" => Ok(3),
+        _ => Err(ParseError::new(0, "expected a register w, x, y or z")),
+    }
+}
+
+impl FromStr for Operand {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match register(s) {
+            Ok(r) => Ok(Operand::Register(r)),
+            Err(_) => parse::number(s).map(Operand::Number),
+        }
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (op, args) = parse::split_pair(s, " ")?;
+        let column = op.len() + 1;
+        if op == "inp" {
+            return register(args)
+                .map(Instruction::Inp)
+                .map_err(|e| e.shifted(column));
+        }
+
+        let (a, b) =
+            parse::pair(args, " ", register, Operand::from_str).map_err(|e| e.shifted(column))?;
+        match op {
+            "add" => Ok(Instruction::Add(a, b)),
+            "mul" => Ok(Instruction::Mul(a, b)),
+            "div" => Ok(Instruction::Div(a, b)),
+            "mod" => Ok(Instruction::Mod(a, b)),
+            "eql" => Ok(Instruction::Eql(a, b)),
+            _ => Err(ParseError::new(0, format!("unknown instruction {:?}", op))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum AluError {
+    MissingInput,
+    DivideByZero,
+    InvalidModulo,
+}
+
+/// Runs the program on the given inputs, returning the final `w, x, y, z`.
+fn execute(program: &[Instruction], input: &[i64]) -> Result<[i64; 4], AluError> {
+    let mut registers = [0; 4];
+    let mut input = input.iter();
+
+    for instruction in program {
+        let value = move |b: Operand| match b {
+            Operand::Register(r) => registers[r],
+            Operand::Number(n) => n,
+        };
+        match *instruction {
+            Instruction::Inp(a) => registers[a] = *input.next().ok_or(AluError::MissingInput)?,
+            Instruction::Add(a, b) => registers[a] += value(b),
+            Instruction::Mul(a, b) => registers[a] *= value(b),
+            Instruction::Div(a, b) => match value(b) {
+                0 => return Err(AluError::DivideByZero),
+                b => registers[a] /= b,
+            },
+            Instruction::Mod(a, b) => match value(b) {
+                b if registers[a] < 0 || b <= 0 => return Err(AluError::InvalidModulo),
+                b => registers[a] %= b,
+            },
+            Instruction::Eql(a, b) => registers[a] = (registers[a] == value(b)) as i64,
+        }
+    }
+    Ok(registers)
+}
+
+fn block_lines(divisor: i64, check: i64, offset: i64) -> Vec<String> {
+    BLOCK
+        .iter()
+        .map(|line| {
+            line.replace("{divisor}", &divisor.to_string())
+                .replace("{check}", &check.to_string())
+                .replace("{offset}", &offset.to_string())
+        })
+        .collect()
+}
+
+/// The `divisor, check, offset` parameters of a block, if it is a MONAD block.
+fn block_parameters(block: &[Instruction]) -> Option<(i64, i64, i64)> {
+    let parameters = match (block.get(4)?, block.get(5)?, block.get(15)?) {
+        (
+            Instruction::Div(Z, Operand::Number(divisor)),
+            Instruction::Add(_, Operand::Number(check)),
+            Instruction::Add(_, Operand::Number(offset)),
+        ) => (*divisor, *check, *offset),
+        _ => return None,
+    };
+
+    let expected = block_lines(parameters.0, parameters.1, parameters.2);
+    let expected: Vec<Instruction> = parse::lines(&expected, Instruction::from_str).ok()?;
+    (block == expected).then_some(parameters)
+}
+
+/// `digits[later] == digits[earlier] + diff`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Constraint {
+    earlier: usize,
+    later: usize,
+    diff: i64,
+}
+
+/// Treats `z` as a base-26 stack: blocks dividing by 1 always push their
+/// digit plus offset, and blocks dividing by 26 pop it and must find their
+/// digit equal to it plus their check, so they do not push again.
+fn constraints(program: &[Instruction]) -> Option<Vec<Constraint>> {
+    if !program.len().is_multiple_of(BLOCK.len()) {
+        return None;
+    }
+
+    let mut stack = Vec::new();
+    let mut constraints = Vec::new();
+    for (digit, block) in program.chunks(BLOCK.len()).enumerate() {
+        match block_parameters(block)? {
+            // A check above 9 can never match a digit, so the block always pushes
+            (1, check, offset) if check > 9 => stack.push((digit, offset)),
+            (26, check, _) => {
+                let (earlier, offset) = stack.pop()?;
+                let constraint = Constraint {
+                    earlier,
+                    later: digit,
+                    diff: offset + check,
+                };
+                event!(
+                    Level::Debug,
+                    "constraint",
+                    earlier = earlier,
+                    later = digit,
+                    diff = constraint.diff
+                );
+                constraints.push(constraint);
+            }
+            _ => return None,
+        }
+    }
+    stack.is_empty().then_some(constraints)
+}
+
+/// The largest or smallest digits satisfying every constraint.
+fn model_number(constraints: &[Constraint], largest: bool) -> Option<Vec<i64>> {
+    let mut digits = vec![0; constraints.len() * 2];
+    for c in constraints {
+        let earlier = if largest {
+            9.min(9 - c.diff)
+        } else {
+            1.max(1 - c.diff)
+        };
+        let later = earlier + c.diff;
+        if !(1..=9).contains(&earlier) || !(1..=9).contains(&later) {
+            return None;
+        }
+        digits[c.earlier] = earlier;
+        digits[c.later] = later;
+    }
+    Some(digits)
+}
+
+fn find_model_number(program: &[Instruction], largest: bool) -> u64 {
+    let constraints = constraints(program).expect("program is not made of MONAD blocks");
+    let digits = model_number(&constraints, largest).expect("no model number is valid");
+
+    let registers = execute(program, &digits).expect("ALU error");
+    assert_eq!(registers[Z], 0, "{:?} rejected by the ALU", digits);
+
+    digits.iter().fold(0, |acc, &d| acc * 10 + d as u64)
+}
+
+fn part1(program: &[Instruction]) -> u64 {
+    find_model_number(program, true)
+}
+
+fn part2(program: &[Instruction]) -> u64 {
+    find_model_number(program, false)
+}
+
+fn parse_input(lines: &[String]) -> ParseResult<Vec<Instruction>> {
+    parse::lines(lines, Instruction::from_str)
+}
+
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    solve_parts!(input, part)
+}
+
+pub fn run(lines: &[String]) {
+    let input = parse_input(lines).unwrap();
+    run_parts!(input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a MONAD program from `(divisor, check, offset)` blocks.
+    fn program(blocks: &[(i64, i64, i64)]) -> Vec<String> {
+        blocks
+            .iter()
+            .flat_map(|&(divisor, check, offset)| block_lines(divisor, check, offset))
+            .collect()
+    }
+
+    fn get_test_input() -> Vec<String> {
+        program(&[
+            (1, 10, 6),
+            (1, 11, 12),
+            (26, -8, 3),
+            (1, 12, 5),
+            (1, 13, 10),
+            (26, -11, 7),
+            (26, -2, 1),
+            (1, 14, 1),
+            (26, 2, 9),
+            (1, 15, 0),
+            (1, 16, 8),
+            (26, -8, 4),
+            (26, 0, 2),
+            (26, -7, 11),
+        ])
+    }
+
+    fn lines(s: &str) -> Vec<String> {
+        s.lines().map(|l| l.trim().to_string()).collect()
+    }
+
+    #[test]
+    fn test_execute() {
+        let negate = parse_input(&lines("inp x\nmul x -1")).unwrap();
+        assert_eq!(execute(&negate, &[7]), Ok([0, -7, 0, 0]));
+        assert_eq!(execute(&negate, &[]), Err(AluError::MissingInput));
+
+        let triple = parse_input(&lines("inp z\ninp x\nmul z 3\neql z x")).unwrap();
+        assert_eq!(execute(&triple, &[2, 6]).map(|r| r[Z]), Ok(1));
+        assert_eq!(execute(&triple, &[2, 5]).map(|r| r[Z]), Ok(0));
+
+        let binary = parse_input(&lines(
+            "inp w
+            add z w
+            mod z 2
+            div w 2
+            add y w
+            mod y 2
+            div w 2
+            add x w
+            mod x 2
+            div w 2
+            mod w 2",
+        ))
+        .unwrap();
+        assert_eq!(execute(&binary, &[13]), Ok([1, 1, 0, 1]));
+
+        let invalid = parse_input(&lines("inp w\nmod w 2")).unwrap();
+        assert_eq!(execute(&invalid, &[-1]), Err(AluError::InvalidModulo));
+        let invalid = parse_input(&lines("inp w\ndiv w x")).unwrap();
+        assert_eq!(execute(&invalid, &[1]), Err(AluError::DivideByZero));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Instruction::from_str("eql x -12"),
+            Ok(Instruction::Eql(1, Operand::Number(-12)))
+        );
+        assert_eq!(Instruction::from_str("add q 1").unwrap_err().column, 4);
+        assert_eq!(Instruction::from_str("add x y1").unwrap_err().column, 6);
+        assert!(Instruction::from_str("sub x 1").is_err());
+    }
+
+    #[test]
+    fn test_brute_force() {
+        let program = parse_input(&program(&[
+            (1, 12, 4),
+            (1, 11, 7),
+            (26, -9, 0),
+            (26, -1, 0),
+        ]))
+        .unwrap();
+        let valid: Vec<u64> = (1111..=9999)
+            .filter(|n: &u64| {
+                let digits: Vec<i64> = n.to_string().bytes().map(|b| (b - b'0') as i64).collect();
+                !digits.contains(&0) && execute(&program, &digits).unwrap()[Z] == 0
+            })
+            .collect();
+        assert_eq!(part1(&program), *valid.last().unwrap());
+        assert_eq!(part2(&program), valid[0]);
+        assert_eq!((part1(&program), part2(&program)), (6979, 1314));
+    }
+
+    #[test]
+    fn test_part1() {
+        let program = parse_input(&get_test_input()).unwrap();
+        assert_eq!(part1(&program), 95969896999998);
+
+        let mut not_monad = program.clone();
+        not_monad[3] = Instruction::Mod(1, Operand::Number(25));
+        assert_eq!(constraints(&not_monad), None);
+    }
+
+    #[test]
+    fn test_part2() {
+        let program = parse_input(&get_test_input()).unwrap();
+        assert_eq!(part2(&program), 21512141411111);
+
+        let mut digits = model_number(&constraints(&program).unwrap(), false).unwrap();
+        digits[13] += 1;
+        assert_ne!(execute(&program, &digits).unwrap()[Z], 0);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::parse::{self, ParseError, ParseResult};
+use crate::trace::Level;
+use ndarray::{Array2, ArrayViewMut2};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cell {
+    Empty,
+    East,
+    South,
+}
+
+/// Moves every sea cucumber of one herd that has an empty cell in front of
+/// it, all at once, wrapping around the edges of the grid.
+fn move_herd(grid: &mut ArrayViewMut2<Cell>, herd: Cell) -> usize {
+    let (rows, cols) = grid.dim();
+    let ahead = |(i, j): (usize, usize)| match herd {
+        Cell::East => (i, (j + 1) % cols),
+        _ => ((i + 1) % rows, j),
+    };
+
+    let movers: Vec<(usize, usize)> = grid
+        .indexed_iter()
+        .filter(|&(index, &cell)| cell == herd && grid[ahead(index)] == Cell::Empty)
+        .map(|(index, _)| index)
+        .collect();
+    for &index in &movers {
+        grid[index] = Cell::Empty;
+        grid[ahead(index)] = herd;
+    }
+    movers.len()
+}
+
+/// Moves the east-facing herd and then the south-facing herd, returning how
+/// many sea cucumbers moved.
+pub fn step(grid: &mut ArrayViewMut2<Cell>) -> usize {
+    let moved = move_herd(grid, Cell::East) + move_herd(grid, Cell::South);
+    event!(Level::Debug, "step", moved = moved);
+    moved
+}
+
+/// The first step on which no sea cucumber moves. Herds that never stop
+/// must eventually repeat a state, which is found with Brent's algorithm:
+/// each state is compared against one saved at the last power of two.
+fn part1(grid: &Array2<Cell>) -> ParseResult<usize> {
+    let mut g = grid.to_owned();
+    let mut saved = g.clone();
+    let mut saved_at = 0;
+    let mut i = 0;
+    loop {
+        i += 1;
+        if step(&mut g.view_mut()) == 0 {
+            event!(Level::Info, "stopped", step = i);
+            return Ok(i);
+        }
+        if g == saved {
+            return Err(ParseError::new(
+                0,
+                format!("never stops: step {} repeats step {}", i, saved_at),
+            ));
+        }
+        if i.is_power_of_two() {
+            saved.assign(&g);
+            saved_at = i;
+        }
+    }
+}
+
+fn parse_input(lines: &[String]) -> ParseResult<Array2<Cell>> {
+    let rows = parse::grid(lines, |c, column| match c {
+        '.' => Ok(Cell::Empty),
+        '>' => Ok(Cell::East),
+        'v' => Ok(Cell::South),
+        _ => Err(ParseError::new(column, format!("unexpected {:?}", c))),
+    })?;
+    let width = rows.first().map_or(0, Vec::len);
+    Ok(Array2::from_shape_vec((rows.len(), width), rows.concat()).unwrap())
+}
+
+/// Day 25 has no second puzzle.
+pub fn solve(lines: &[String], part: u8) -> ParseResult<Option<String>> {
+    let input = parse_input(lines)?;
+    Ok(match part {
+        1 => Some(part1(&input)?.to_string()),
+        _ => None,
+    })
+}
+
+pub fn run(lines: &[String]) {
+    let input = parse_input(lines).unwrap();
+    run_parts!(input; try part1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_test_input() -> Vec<String> {
+        vec![
+            "v...>>.vv>".to_string(),
+            ".vv>>.vv..".to_string(),
+            ">>.>v>...v".to_string(),
+            ">>v>>.>.v.".to_string(),
+            "v>v.vv.v..".to_string(),
+            ">.>>..v...".to_string(),
+            ".vv..>.>v.".to_string(),
+            "v.v..>>v.v".to_string(),
+            "....v..v.>".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_step() {
+        let mut grid = parse_input(&["...>>>>>...".to_string()]).unwrap();
+        assert_eq!(step(&mut grid.view_mut()), 1);
+        assert_eq!(grid, parse_input(&["...>>>>.>..".to_string()]).unwrap());
+        assert_eq!(step(&mut grid.view_mut()), 2);
+        assert_eq!(grid, parse_input(&["...>>>.>.>.".to_string()]).unwrap());
+
+        // Both herds wrap around, and the south herd sees where the east herd moved to
+        let mut grid =
+            parse_input(&["..>".to_string(), "...".to_string(), "..v".to_string()]).unwrap();
+        assert_eq!(step(&mut grid.view_mut()), 2);
+        assert_eq!(
+            grid,
+            parse_input(&[">.v".to_string(), "...".to_string(), "...".to_string()]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut lines = get_test_input();
+        lines[2] = ">>.>v>..<v".to_string();
+        let err = parse_input(&lines).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 8));
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(&parse_input(&get_test_input()).unwrap()), Ok(58));
+        assert_eq!(solve(&get_test_input(), 2), Ok(None));
+
+        let err = part1(&parse_input(&[">.".to_string()]).unwrap()).unwrap_err();
+        assert_eq!(err.message, "never stops: step 4 repeats step 2");
+        let lines = [">..v".to_string(), "....".to_string(), "v..>".to_string()];
+        assert!(solve(&lines, 1).is_err());
+    }
+}
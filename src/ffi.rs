@@ -0,0 +1,129 @@
+//! C ABI for the solvers. The header in `include/aoc.h` is generated from this
+//! file with `cbindgen --config cbindgen.toml --output include/aoc.h src/ffi.rs`.
+
+use crate::SolveError;
+use std::os::raw::c_char;
+
+/// The requested day or part does not exist.
+pub const AOC_ERR_UNKNOWN_PUZZLE: i64 = -1;
+/// The input is not valid UTF-8, or a required pointer is null.
+pub const AOC_ERR_INVALID_INPUT: i64 = -2;
+/// The solver failed on the given input.
+pub const AOC_ERR_SOLVER_FAILED: i64 = -3;
+/// The input could not be parsed as that day's puzzle input.
+pub const AOC_ERR_PARSE_FAILED: i64 = -4;
+
+/// The answer, or an error code with an optional message for the caller.
+fn solve(day: u8, part: u8, input: &[u8]) -> Result<String, (i64, Option<String>)> {
+    let input = std::str::from_utf8(input).map_err(|_| (AOC_ERR_INVALID_INPUT, None))?;
+    crate::solve_input(day, part, input).map_err(|e| match e {
+        SolveError::UnknownPuzzle => (AOC_ERR_UNKNOWN_PUZZLE, None),
+        SolveError::InvalidInput(e) => (AOC_ERR_PARSE_FAILED, Some(e.to_string())),
+        SolveError::SolverFailed => (AOC_ERR_SOLVER_FAILED, None),
+    })
+}
+
+/// Copies `s` into `out_buf` as a NUL-terminated string, truncated to fit.
+unsafe fn write_out(s: &str, out_buf: *mut c_char, out_len: usize) {
+    if out_len > 0 {
+        let n = s.len().min(out_len - 1);
+        std::ptr::copy_nonoverlapping(s.as_ptr(), out_buf as *mut u8, n);
+        *out_buf.add(n) = 0;
+    }
+}
+
+/// Solves `part` of `day` for the puzzle input in `input_ptr[0..input_len]`.
+///
+/// The answer is written to `out_buf` as a NUL-terminated string, truncated
+/// to fit `out_len` bytes. Returns the length of the full answer (excluding
+/// the NUL), so a return value `>= out_len` means the answer was truncated.
+/// Returns one of the negative `AOC_ERR_*` codes on failure; for
+/// `AOC_ERR_PARSE_FAILED` the parse error, with its line and column, is
+/// written to `out_buf` in place of the answer.
+///
+/// # Safety
+///
+/// `input_ptr` must point to `input_len` readable bytes, and `out_buf` must
+/// point to `out_len` writable bytes (it may be null if `out_len` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn aoc_solve(
+    day: u8,
+    part: u8,
+    input_ptr: *const u8,
+    input_len: usize,
+    out_buf: *mut c_char,
+    out_len: usize,
+) -> i64 {
+    if input_ptr.is_null() || (out_buf.is_null() && out_len > 0) {
+        return AOC_ERR_INVALID_INPUT;
+    }
+
+    let input = std::slice::from_raw_parts(input_ptr, input_len);
+    match solve(day, part, input) {
+        Ok(answer) => {
+            write_out(&answer, out_buf, out_len);
+            answer.len() as i64
+        }
+        Err((code, message)) => {
+            write_out(message.as_deref().unwrap_or_default(), out_buf, out_len);
+            code
+        }
+    }
+}
+
+/// Returns the highest day number that `aoc_solve` accepts.
+#[no_mangle]
+pub extern "C" fn aoc_max_day() -> u8 {
+    (1..=u8::MAX)
+        .take_while(|&day| crate::solver(day).is_some())
+        .last()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(day: u8, part: u8, input: &str, out: &mut [u8]) -> i64 {
+        unsafe {
+            aoc_solve(
+                day,
+                part,
+                input.as_ptr(),
+                input.len(),
+                out.as_mut_ptr() as *mut c_char,
+                out.len(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        let mut out = [0xffu8; 16];
+        assert_eq!(call(7, 2, "16,1,2,0,4,2,7,1,2,14", &mut out), 3);
+        assert_eq!(&out[..4], b"168\0");
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut out = [0xffu8; 3];
+        assert_eq!(call(16, 1, "A0016C880162017C3686B18A3D4780", &mut out), 2);
+        assert_eq!(&out, b"31\0");
+        assert_eq!(call(6, 2, "3,4,3,1,2", &mut out), 11);
+        assert_eq!(&out, b"26\0");
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut out = [0u8; 64];
+        assert_eq!(call(0, 1, "", &mut out), AOC_ERR_UNKNOWN_PUZZLE);
+        assert_eq!(call(7, 3, "1,2", &mut out), AOC_ERR_UNKNOWN_PUZZLE);
+        assert_eq!(call(7, 1, "1,x", &mut out), AOC_ERR_PARSE_FAILED);
+        assert!(out.starts_with(b"line 1, column 3: invalid number \"x\"\0"));
+        assert_eq!(call(1, 1, "", &mut out), 1);
+        assert_eq!(&out[..2], b"0\0");
+        // Vents beyond the 1000x1000 floor parse, but the solver panics
+        assert_eq!(call(5, 1, "0,0 -> 0,5000", &mut out), AOC_ERR_SOLVER_FAILED);
+        assert_eq!(aoc_max_day(), 25);
+    }
+}
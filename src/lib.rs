@@ -1,14 +1,74 @@
+use parse::{ParseError, ParseResult};
 use std::fmt::Display;
 use std::time::Duration;
 
+/// Solves one part of a day, or returns `None` if the day has no such part.
+pub type Solver = fn(&[String], u8) -> ParseResult<Option<String>>;
+
 pub fn input_lines(filename: &str) -> Vec<String> {
-    std::fs::read_to_string(filename)
-        .expect("file not found")
+    split_lines(&std::fs::read_to_string(filename).expect("file not found"))
+}
+
+pub fn split_lines(input: &str) -> Vec<String> {
+    input
         .lines()
         .map(|line| line.parse().unwrap())
         .collect::<Vec<String>>()
 }
 
+/// Looks up the `solve` function of the given day.
+pub fn solver(day: u8) -> Option<Solver> {
+    let solve: Solver = match day {
+        1 => day01::solve,
+        2 => day02::solve,
+        3 => day03::solve,
+        4 => day04::solve,
+        5 => day05::solve,
+        6 => day06::solve,
+        7 => day07::solve,
+        8 => day08::solve,
+        9 => day09::solve,
+        10 => day10::solve,
+        11 => day11::solve,
+        12 => day12::solve,
+        13 => day13::solve,
+        14 => day14::solve,
+        15 => day15::solve,
+        16 => day16::solve,
+        17 => day17::solve,
+        18 => day18::solve,
+        19 => day19::solve,
+        20 => day20::solve,
+        21 => day21::solve,
+        22 => day22::solve,
+        23 => day23::solve,
+        24 => day24::solve,
+        25 => day25::solve,
+        _ => return None,
+    };
+    Some(solve)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SolveError {
+    UnknownPuzzle,
+    InvalidInput(ParseError),
+    SolverFailed,
+}
+
+/// Solves one part of a day for the given raw input. Invalid input is
+/// reported as a positioned parse error; a panic in the solver itself is
+/// still caught, as a last resort, and reported as `SolverFailed`.
+pub fn solve_input(day: u8, part: u8, input: &str) -> Result<String, SolveError> {
+    let solve = solver(day).ok_or(SolveError::UnknownPuzzle)?;
+    let lines = split_lines(input);
+
+    std::panic::catch_unwind(|| solve(&lines, part))
+        .map_err(|_| SolveError::SolverFailed)?
+        .map_err(SolveError::InvalidInput)?
+        .ok_or(SolveError::UnknownPuzzle)
+}
+
 pub struct MyDuration(pub Duration);
 
 impl Display for MyDuration {
@@ -39,31 +99,111 @@ impl Display for MyDuration {
 macro_rules! day {
     ($x:ident) => {
         let day_number = &stringify!($x)[3..5];
-        $x::run(&lib::input_lines(
-            &format!("inputs/{}.txt", day_number).to_string(),
-        ))
+        let path = format!("inputs/{}.txt", day_number);
+        if std::path::Path::new(&path).exists() {
+            $crate::$x::run(&$crate::input_lines(&path))
+        } else {
+            println!("────┼{:─<60}┼{:─<10}", "", "");
+            println!(
+                "{:>3} │ {:58} │",
+                day_number.trim_start_matches('0'),
+                format!("No input at {}", path)
+            );
+        }
     };
 }
 
+/// Solves the given part with `part1` or `part2`. With `try`, the parts
+/// return a `ParseResult` whose error is passed on.
+#[macro_export]
+macro_rules! solve_parts {
+    ($input:ident, $part:ident) => {
+        Ok(match $part {
+            1 => Some(part1(&$input).to_string()),
+            2 => Some(part2(&$input).to_string()),
+            _ => None,
+        })
+    };
+    ($input:ident, $part:ident; try) => {
+        Ok(match $part {
+            1 => Some(part1(&$input)?.to_string()),
+            2 => Some(part2(&$input)?.to_string()),
+            _ => None,
+        })
+    };
+}
+
+/// Prints a row of the results table for each part, calling `part1` and
+/// `part2` unless other functions are given for the parts, in order. With
+/// `try`, the parts return a `ParseResult` and an error is printed in place
+/// of the answer.
 #[macro_export]
 macro_rules! run_parts {
     ($input:ident) => {
+        $crate::run_parts!($input; part1, part2);
+    };
+    ($input:ident; try) => {
+        $crate::run_parts!($input; try part1, part2);
+    };
+    ($input:ident; try $($part_fn:path),+) => {
+        $crate::run_parts!(@rows $input; $(|input| match $part_fn(input) {
+            Ok(answer) => answer.to_string(),
+            Err(e) => format!("error: {}", e),
+        }),+);
+    };
+    ($input:ident; $($part_fn:path),+) => {
+        $crate::run_parts!(@rows $input; $($part_fn),+);
+    };
+    (@rows $input:ident; $($part_fn:expr),+) => {
         let module = module_path!().split("::").last().unwrap()[3..]
             .parse::<u8>()
             .unwrap();
 
-        let part1_start = std::time::Instant::now();
-        let part1_answer = part1(&$input);
-        let part1_time = crate::lib::MyDuration(part1_start.elapsed());
-        let part2_start = std::time::Instant::now();
-        let part2_answer = part2(&$input);
-        let part2_time = crate::lib::MyDuration(part2_start.elapsed());
-
         println!("────┼{:─<60}┼{:─<10}", "", "");
-        println!(
-            "{:3} │ Part 1: {:50} │ {} ",
-            module, part1_answer, part1_time
-        );
-        println!("{:3} │ Part 2: {:50} │ {} ", "", part2_answer, part2_time);
+        let mut part = 0;
+        $(
+            part += 1;
+            let start = std::time::Instant::now();
+            let answer = ($part_fn)(&$input);
+            let time = $crate::MyDuration(start.elapsed());
+            let day = if part == 1 { module.to_string() } else { String::new() };
+            println!("{:>3} │ Part {}: {:>50} │ {} ", day, part, answer, time);
+        )+
     };
 }
+
+#[macro_use]
+pub mod trace;
+
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod ffi;
+pub mod parse;
+pub mod profile;
+pub mod repl;
+pub mod serve;
+#[doc(hidden)]
+pub mod testutil;
@@ -1,24 +1,6 @@
-#[macro_use]
-mod lib;
+use aoc_2021::{day, day01, profile};
 
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-
-fn main() {
+fn run_all() {
     let start = std::time::Instant::now();
     println!("━━━━┯{:━<60}┯{:━<10}", "", "");
     println!("Day │{:^60}│{:^10}", "Answer", "Time");
@@ -38,7 +20,118 @@ fn main() {
     day!(day14);
     day!(day15);
     day!(day16);
+    day!(day17);
+    day!(day18);
+    day!(day19);
+    day!(day20);
+    day!(day21);
+    day!(day22);
+    day!(day23);
+    day!(day24);
+    day!(day25);
     let time = start.elapsed();
     println!("━━━━┷{:━<60}┷{:━<10}", "", "");
     println!("\nTotal time elapsed: {:?}", time);
 }
+
+fn run_profile(name: &str) {
+    let profile = match profile::load(name) {
+        Ok(profile) => profile,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let start = std::time::Instant::now();
+    let within_limits = profile::run(&profile, &mut std::io::stdout()).expect("I/O error");
+    if profile.format == profile::Format::Table {
+        println!("\nTotal time elapsed: {:?}", start.elapsed());
+    }
+    if !within_limits {
+        std::process::exit(1);
+    }
+}
+
+fn main() {
+    let mut flags = Vec::new();
+    let mut args = Vec::new();
+    let mut profile = None;
+    let mut argv = std::env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        if arg == "--profile" {
+            profile = argv.next();
+            if profile.is_none() {
+                eprintln!("--profile needs a profile name");
+                std::process::exit(2);
+            }
+        } else if let Some(name) = arg.strip_prefix("--profile=") {
+            profile = Some(name.to_string());
+        } else if arg.starts_with('-') {
+            flags.push(arg);
+        } else {
+            args.push(arg);
+        }
+    }
+
+    let mut verbosity = 0;
+    for flag in &flags {
+        match flag.as_str() {
+            "-v" | "-vv" | "-vvv" => verbosity += flag.len() as u8 - 1,
+            "--verbose" => verbosity += 1,
+            _ => {
+                eprintln!("Unknown flag: {}", flag);
+                std::process::exit(2);
+            }
+        }
+    }
+    aoc_2021::trace::set_verbosity(verbosity);
+
+    match (args.first().map(String::as_str), profile) {
+        (Some("serve"), None) => {
+            let addr = args
+                .get(1)
+                .map_or(aoc_2021::serve::DEFAULT_ADDR, String::as_str);
+            aoc_2021::serve::serve(addr).expect("server failed");
+        }
+        (Some("repl"), None) => {
+            let stdin = std::io::stdin();
+            aoc_2021::repl::repl(stdin.lock(), std::io::stdout()).expect("I/O error");
+        }
+        (Some("depths"), None) => {
+            let path = args.get(1).map_or("inputs/01.txt", String::as_str);
+            let input = match std::fs::read_to_string(path) {
+                Ok(input) => input,
+                Err(e) => {
+                    eprintln!("{}: {}", path, e);
+                    std::process::exit(2);
+                }
+            };
+            match day01::parse_input(&aoc_2021::split_lines(&input)) {
+                Ok(depths) => println!(
+                    "{}",
+                    day01::depth_profile(&depths, day01::OUTLIER_WINDOW, day01::OUTLIER_THRESHOLD)
+                ),
+                Err(e) => {
+                    eprintln!("{}: {}", path, e);
+                    std::process::exit(2);
+                }
+            }
+        }
+        (None, Some(name)) => run_profile(&name),
+        (None, None) => run_all(),
+        (Some(command @ ("serve" | "repl" | "depths")), Some(_)) => {
+            eprintln!("--profile cannot be combined with the {} command", command);
+            usage();
+        }
+        (Some(other), _) => {
+            eprintln!("Unknown command: {}", other);
+            usage();
+        }
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: aoc-2021 [-v | -vv] [--profile NAME | serve [ADDR] | repl | depths [FILE]]");
+    std::process::exit(2);
+}
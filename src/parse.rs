@@ -0,0 +1,287 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// A parse failure, positioned at a (zero-based) line and column of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+impl ParseError {
+    pub fn new(column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line: 0,
+            column,
+            message: message.into(),
+        }
+    }
+
+    pub fn shifted(mut self, columns: usize) -> Self {
+        self.column += columns;
+        self
+    }
+
+    pub fn on_line(mut self, line: usize) -> Self {
+        self.line += line;
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line + 1,
+            self.column + 1,
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses a single value, ignoring surrounding whitespace.
+pub fn number<T: FromStr>(s: &str) -> ParseResult<T> {
+    let start = s.len() - s.trim_start().len();
+    let trimmed = s.trim();
+    trimmed
+        .parse()
+        .map_err(|_| ParseError::new(start, format!("invalid number {:?}", trimmed)))
+}
+
+/// Parses a single decimal digit.
+pub fn digit(c: char, column: usize) -> ParseResult<usize> {
+    c.to_digit(10)
+        .map(|d| d as usize)
+        .ok_or_else(|| ParseError::new(column, format!("expected digit, found {:?}", c)))
+}
+
+/// Strips `prefix` from the start of `s`.
+pub fn tag<'a>(s: &'a str, prefix: &str) -> ParseResult<&'a str> {
+    s.strip_prefix(prefix)
+        .ok_or_else(|| ParseError::new(0, format!("expected {:?}", prefix)))
+}
+
+/// Splits `s` at the first `sep` and returns both halves.
+pub fn split_pair<'a>(s: &'a str, sep: &str) -> ParseResult<(&'a str, &'a str)> {
+    s.split_once(sep)
+        .ok_or_else(|| ParseError::new(0, format!("expected separator {:?}", sep)))
+}
+
+/// Splits `s` at the first `sep` and parses each half.
+pub fn pair<'a, A, B>(
+    s: &'a str,
+    sep: &str,
+    first: impl Fn(&'a str) -> ParseResult<A>,
+    second: impl Fn(&'a str) -> ParseResult<B>,
+) -> ParseResult<(A, B)> {
+    let (a, b) = split_pair(s, sep)?;
+    let offset = a.len() + sep.len();
+    Ok((first(a)?, second(b).map_err(|e| e.shifted(offset))?))
+}
+
+/// Parses an inclusive range such as `-10..-5`.
+pub fn range<T: FromStr + PartialOrd>(s: &str) -> ParseResult<(T, T)> {
+    let (low, high) = pair(s, "..", number, number)?;
+    if low > high {
+        return Err(ParseError::new(0, "range is reversed"));
+    }
+    Ok((low, high))
+}
+
+/// Parses a `key=value` pair, returning the key and the parsed value.
+pub fn key_value<'a, T>(
+    s: &'a str,
+    value: impl Fn(&'a str) -> ParseResult<T>,
+) -> ParseResult<(&'a str, T)> {
+    pair(s, "=", |k| Ok(k.trim()), value)
+}
+
+/// Parses every `sep`-separated item of `s`.
+pub fn list<'a, T>(
+    s: &'a str,
+    sep: &str,
+    item: impl Fn(&'a str) -> ParseResult<T>,
+) -> ParseResult<Vec<T>> {
+    let mut offset = 0;
+    s.split(sep)
+        .map(|part| {
+            let start = offset;
+            offset += part.len() + sep.len();
+            item(part).map_err(|e| e.shifted(start))
+        })
+        .collect()
+}
+
+/// Parses a `sep`-separated list of integers, such as `3,4,3,1,2`.
+pub fn integer_list<T: FromStr>(s: &str, sep: &str) -> ParseResult<Vec<T>> {
+    list(s, sep, number)
+}
+
+/// Parses a whitespace-separated list of integers, such as `22 13 17 11  0`.
+pub fn whitespace_list<T: FromStr>(s: &str) -> ParseResult<Vec<T>> {
+    let base = s.as_ptr() as usize;
+    s.split_whitespace()
+        .map(|part| number(part).map_err(|e| e.shifted(part.as_ptr() as usize - base)))
+        .collect()
+}
+
+/// Parses every line with `f`, tagging errors with the offending line.
+pub fn lines<'a, T>(
+    lines: &'a [String],
+    f: impl Fn(&'a str) -> ParseResult<T>,
+) -> ParseResult<Vec<T>> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| f(line).map_err(|e| e.on_line(i)))
+        .collect()
+}
+
+/// The first line of a single-line input.
+pub fn first_line(input: &[String]) -> ParseResult<&str> {
+    input
+        .first()
+        .map(|s| s.as_str())
+        .ok_or_else(|| ParseError::new(0, "empty input"))
+}
+
+/// A block of input lines separated from its neighbours by empty lines.
+pub struct Section<'a> {
+    pub start: usize,
+    pub lines: &'a [String],
+}
+
+impl<'a> Section<'a> {
+    pub fn parse<T>(&self, f: impl Fn(&'a str) -> ParseResult<T>) -> ParseResult<Vec<T>> {
+        lines(self.lines, f).map_err(|e| e.on_line(self.start))
+    }
+
+    pub fn first(&self) -> ParseResult<&'a str> {
+        self.lines
+            .first()
+            .map(|s| s.as_str())
+            .ok_or_else(|| ParseError::new(0, "empty section").on_line(self.start))
+    }
+}
+
+/// Splits the input into sections at empty lines.
+pub fn sections(lines: &[String]) -> Vec<Section<'_>> {
+    let mut start = 0;
+    lines
+        .split(|line| line.is_empty())
+        .map(|section| {
+            let s = Section {
+                start,
+                lines: section,
+            };
+            start += section.len() + 1;
+            s
+        })
+        .filter(|s| !s.lines.is_empty())
+        .collect()
+}
+
+/// Parses exactly `n` sections, failing if the input has a different number.
+pub fn n_sections(input: &[String], n: usize) -> ParseResult<Vec<Section<'_>>> {
+    let s = sections(input);
+    if s.len() != n {
+        return Err(
+            ParseError::new(0, format!("expected {} sections, found {}", n, s.len()))
+                .on_line(input.len()),
+        );
+    }
+    Ok(s)
+}
+
+/// Parses a rectangular grid with one `cell` per character, which is given
+/// the character and its column.
+pub fn grid<T>(
+    input: &[String],
+    cell: impl Fn(char, usize) -> ParseResult<T>,
+) -> ParseResult<Vec<Vec<T>>> {
+    let width = input.first().map_or(0, |l| l.len());
+    lines(input, |line| {
+        if line.len() != width {
+            return Err(ParseError::new(
+                line.len().min(width),
+                format!("expected {} columns, found {}", width, line.len()),
+            ));
+        }
+        line.chars().enumerate().map(|(j, c)| cell(c, j)).collect()
+    })
+}
+
+/// Parses a rectangular grid of single digits.
+pub fn digit_grid(input: &[String]) -> ParseResult<Vec<Vec<usize>>> {
+    grid(input, digit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_list() {
+        assert_eq!(
+            integer_list::<usize>("3,4,3,1,2", ","),
+            Ok(vec![3, 4, 3, 1, 2])
+        );
+        let err = integer_list::<usize>("3,4,x,1", ",").unwrap_err();
+        assert_eq!((err.line, err.column), (0, 4));
+    }
+
+    #[test]
+    fn test_pair() {
+        let p = pair(
+            "0,9 -> 5,9",
+            " -> ",
+            |s| integer_list::<isize>(s, ","),
+            |s| integer_list::<isize>(s, ","),
+        );
+        assert_eq!(p, Ok((vec![0, 9], vec![5, 9])));
+
+        let err = pair("0,9 -> 5,y", " -> ", split_pair_numbers, split_pair_numbers).unwrap_err();
+        assert_eq!(err.column, 9);
+    }
+
+    fn split_pair_numbers(s: &str) -> ParseResult<(isize, isize)> {
+        pair(s, ",", number, number)
+    }
+
+    #[test]
+    fn test_key_value() {
+        let fold = tag("fold along x=5", "fold along ").and_then(|s| key_value(s, number::<usize>));
+        assert_eq!(fold, Ok(("x", 5)));
+        assert!(tag("unfold x=5", "fold along ").is_err());
+    }
+
+    #[test]
+    fn test_sections_and_grid() {
+        let input: Vec<String> = vec!["12", "34", "", "5a"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let s = sections(&input);
+        assert_eq!(s.len(), 2);
+        assert_eq!(digit_grid(s[0].lines), Ok(vec![vec![1, 2], vec![3, 4]]));
+        let err = s[1]
+            .parse(|l| {
+                l.chars()
+                    .enumerate()
+                    .map(|(j, c)| digit(c, j))
+                    .collect::<ParseResult<Vec<_>>>()
+            })
+            .unwrap_err();
+        assert_eq!((err.line, err.column), (3, 1));
+        assert_eq!(
+            err.to_string(),
+            "line 4, column 2: expected digit, found 'a'"
+        );
+    }
+}
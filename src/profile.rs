@@ -0,0 +1,464 @@
+//! Named run profiles, read from the `[profile.NAME]` tables of `aoc.toml`
+//! and selected with `--profile NAME`.
+
+use crate::parse;
+use crate::serve::json_string;
+use crate::MyDuration;
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use toml::Value;
+
+pub const CONFIG_FILE: &str = "aoc.toml";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// The table printed when running without a profile.
+    Table,
+    /// One JSON object per part, as answered by `serve`.
+    Json,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Profile {
+    pub name: String,
+    pub days: Vec<u8>,
+    /// Path of each day's input, with `{day}` replaced by the two-digit day.
+    pub input: String,
+    /// Where the table format writes multi-line answers, like `input`.
+    pub output: String,
+    pub format: Format,
+    /// How many times each part is solved; the mean time is reported.
+    pub iterations: u32,
+    /// Parts whose mean time exceeds this fail the run.
+    pub time_limit: Option<Duration>,
+}
+
+impl Profile {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            days: (1..)
+                .take_while(|&day| crate::solver(day).is_some())
+                .collect(),
+            input: "inputs/{day}.txt".to_string(),
+            output: "outputs/{day}.txt".to_string(),
+            format: Format::Table,
+            iterations: 1,
+            time_limit: None,
+        }
+    }
+
+    pub fn input_path(&self, day: u8) -> String {
+        self.input.replace("{day}", &format!("{:02}", day))
+    }
+
+    pub fn output_path(&self, day: u8) -> String {
+        self.output.replace("{day}", &format!("{:02}", day))
+    }
+}
+
+#[derive(Debug)]
+pub enum ProfileError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    /// A value that is valid TOML but not a valid setting, at a dotted
+    /// key such as `profile.ci.days`.
+    Invalid {
+        key: String,
+        message: String,
+    },
+    UnknownProfile {
+        name: String,
+        available: Vec<String>,
+    },
+}
+
+impl ProfileError {
+    fn invalid(key: &str, message: impl Into<String>) -> Self {
+        ProfileError::Invalid {
+            key: key.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProfileError::Io(e) => write!(f, "{}: {}", CONFIG_FILE, e),
+            ProfileError::Toml(e) => write!(f, "{}: {}", CONFIG_FILE, e),
+            ProfileError::Invalid { key, message } => {
+                write!(f, "{}: {}: {}", CONFIG_FILE, key, message)
+            }
+            ProfileError::UnknownProfile { name, available } => write!(
+                f,
+                "no profile {:?} in {} (available: {})",
+                name,
+                CONFIG_FILE,
+                available.join(", ")
+            ),
+        }
+    }
+}
+
+fn days(value: &Value) -> Result<Vec<u8>, String> {
+    let days: Vec<i64> = match value {
+        Value::String(range) => {
+            let (first, last) = parse::range(range).map_err(|e| e.message)?;
+            (first..=last).collect()
+        }
+        Value::Array(days) => days
+            .iter()
+            .map(|day| day.as_integer().ok_or("expected an integer day"))
+            .collect::<Result<_, _>>()?,
+        _ => return Err("expected an array of days or an \"a..b\" range".to_string()),
+    };
+    days.into_iter()
+        .map(|day| match day {
+            1..=25 => Ok(day as u8),
+            _ => Err("expected days between 1 and 25".to_string()),
+        })
+        .collect()
+}
+
+fn set(profile: &mut Profile, key: &str, value: &Value) -> Result<(), String> {
+    let positive = |value: &Value| match value.as_integer() {
+        Some(n) if n > 0 => Ok(n as u64),
+        _ => Err("expected a positive integer".to_string()),
+    };
+    let string = |value: &Value| {
+        value
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "expected a string".to_string())
+    };
+
+    match key {
+        "days" => profile.days = days(value)?,
+        "input" => profile.input = string(value)?,
+        "output" => profile.output = string(value)?,
+        "format" => {
+            profile.format = match string(value)?.as_str() {
+                "table" => Format::Table,
+                "json" => Format::Json,
+                _ => return Err("expected \"table\" or \"json\"".to_string()),
+            }
+        }
+        "iterations" => {
+            profile.iterations = u32::try_from(positive(value)?)
+                .map_err(|_| format!("expected at most {}", u32::MAX))?
+        }
+        "time_limit_ms" => profile.time_limit = Some(Duration::from_millis(positive(value)?)),
+        _ => return Err("unknown key".to_string()),
+    }
+    Ok(())
+}
+
+/// Parses the `[profile.NAME]` tables of a config file, ordered by name.
+pub fn parse_profiles(config: &str) -> Result<Vec<Profile>, ProfileError> {
+    let config: toml::value::Table = toml::from_str(config).map_err(ProfileError::Toml)?;
+    let mut profiles = Vec::new();
+
+    for (key, value) in &config {
+        let tables = match (key.as_str(), value) {
+            ("profile", Value::Table(tables)) => tables,
+            _ => return Err(ProfileError::invalid(key, "expected [profile.NAME] tables")),
+        };
+        for (name, settings) in tables {
+            let path = format!("profile.{}", name);
+            let settings = settings
+                .as_table()
+                .ok_or_else(|| ProfileError::invalid(&path, "expected a table"))?;
+            let mut profile = Profile::new(name);
+            for (key, value) in settings {
+                set(&mut profile, key, value)
+                    .map_err(|e| ProfileError::invalid(&format!("{}.{}", path, key), e))?;
+            }
+            profiles.push(profile);
+        }
+    }
+
+    Ok(profiles)
+}
+
+/// Reads the named profile from `aoc.toml` in the working directory.
+pub fn load(name: &str) -> Result<Profile, ProfileError> {
+    let config = std::fs::read_to_string(CONFIG_FILE).map_err(ProfileError::Io)?;
+    let profiles = parse_profiles(&config)?;
+    let available = profiles.iter().map(|p| p.name.clone()).collect();
+    profiles
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| ProfileError::UnknownProfile {
+            name: name.to_string(),
+            available,
+        })
+}
+
+/// Writes a row for a day that could not be solved.
+fn write_error(out: &mut impl Write, format: Format, day: u8, message: &str) -> io::Result<()> {
+    match format {
+        Format::Table => {
+            writeln!(out, "────┼{:─<60}┼{:─<10}", "", "")?;
+            writeln!(out, "{:>3} │ {:58} │", day, message)
+        }
+        Format::Json => writeln!(
+            out,
+            "{{\"day\":{},\"error\":{}}}",
+            day,
+            json_string(message)
+        ),
+    }
+}
+
+/// Solves the profile's days, writing the answers and mean times in its
+/// format. Returns whether every part stayed within the time limit.
+pub fn run(profile: &Profile, out: &mut impl Write) -> io::Result<bool> {
+    let mut over_limit = Vec::new();
+
+    if profile.format == Format::Table {
+        writeln!(out, "━━━━┯{:━<60}┯{:━<10}", "", "")?;
+        writeln!(out, "Day │{:^60}│{:^10}", "Answer", "Time")?;
+    }
+
+    for &day in &profile.days {
+        let path = profile.input_path(day);
+        let solve = match crate::solver(day) {
+            Some(solve) if Path::new(&path).exists() => solve,
+            _ => {
+                let message = format!("No input at {}", path);
+                write_error(out, profile.format, day, &message)?;
+                continue;
+            }
+        };
+        let lines = crate::input_lines(&path);
+        // There is no part 0, so this only parses the input
+        if let Err(e) = solve(&lines, 0) {
+            write_error(out, profile.format, day, &format!("{}: {}", path, e))?;
+            continue;
+        }
+
+        if profile.format == Format::Table {
+            writeln!(out, "────┼{:─<60}┼{:─<10}", "", "")?;
+        }
+        for part in 1..=2 {
+            let start = Instant::now();
+            let mut answer = None;
+            for _ in 0..profile.iterations {
+                answer = solve(&lines, part).expect("input already parsed");
+            }
+            let time = start.elapsed() / profile.iterations;
+            let answer = match answer {
+                Some(answer) => answer,
+                None => continue,
+            };
+
+            let within_limit = profile.time_limit.is_none_or(|limit| time <= limit);
+            if !within_limit {
+                over_limit.push((day, part, time));
+            }
+
+            match profile.format {
+                Format::Table => {
+                    // Multi-line answers (day 13's letters) would break the
+                    // table, so they go to a file as in the default run
+                    let answer = if answer.contains('\n') {
+                        let path = profile.output_path(day);
+                        std::fs::write(&path, &answer)?;
+                        format!("See {}", path)
+                    } else {
+                        answer
+                    };
+                    let label = if part == 1 {
+                        day.to_string()
+                    } else {
+                        String::new()
+                    };
+                    let marker = if within_limit { ' ' } else { '!' };
+                    writeln!(
+                        out,
+                        "{:>3} │ Part {}: {:>50} │ {}{}",
+                        label,
+                        part,
+                        answer,
+                        MyDuration(time),
+                        marker
+                    )?
+                }
+                Format::Json => writeln!(
+                    out,
+                    "{{\"day\":{},\"part\":{},\"answer\":{},\"time_ns\":{},\"iterations\":{},\"within_limit\":{}}}",
+                    day,
+                    part,
+                    json_string(&answer),
+                    time.as_nanos(),
+                    profile.iterations,
+                    within_limit
+                )?,
+            }
+        }
+    }
+
+    if profile.format == Format::Table {
+        writeln!(out, "━━━━┷{:━<60}┷{:━<10}", "", "")?;
+        if let Some(limit) = profile.time_limit {
+            for (day, part, time) in &over_limit {
+                writeln!(
+                    out,
+                    "Day {} part {} took {}, over the {} limit",
+                    day,
+                    part,
+                    MyDuration(*time).to_string().trim(),
+                    MyDuration(limit).to_string().trim()
+                )?;
+            }
+        }
+    }
+    Ok(over_limit.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+# Fast days only
+[profile.quick]
+days = [1, 2]   # day 1 and 2
+input = "inputs/{day}.txt"
+time_limit_ms = 50
+
+[profile.ci]
+days = "1..16"
+format = "json"
+iterations = 3
+"#;
+
+    #[test]
+    fn test_parse() {
+        let profiles = parse_profiles(CONFIG).unwrap();
+        assert_eq!(profiles.len(), 2);
+
+        assert_eq!(profiles[1].name, "quick");
+        assert_eq!(profiles[1].days, [1, 2]);
+        assert_eq!(profiles[1].input_path(2), "inputs/02.txt");
+        assert_eq!(profiles[1].format, Format::Table);
+        assert_eq!(profiles[1].iterations, 1);
+        assert_eq!(profiles[1].time_limit, Some(Duration::from_millis(50)));
+
+        assert_eq!(profiles[0].name, "ci");
+        assert_eq!(profiles[0].days, (1..=16).collect::<Vec<_>>());
+        assert_eq!(profiles[0].format, Format::Json);
+        assert_eq!(profiles[0].iterations, 3);
+        assert_eq!(profiles[0].time_limit, None);
+
+        // Any TOML spelling of the same tables is accepted
+        let inline = "profile = { quick = { days = [\n  1,\n  2,\n], input = 'x' } }";
+        let profiles = parse_profiles(inline).unwrap();
+        assert_eq!(profiles[0].days, [1, 2]);
+        assert_eq!(profiles[0].input, "x");
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |config: &str| match parse_profiles(config).unwrap_err() {
+            ProfileError::Invalid { key, message } => format!("{}: {}", key, message),
+            ProfileError::Toml(e) => format!("{:?}", e.line_col()),
+            e => panic!("unexpected error {:?}", e),
+        };
+        assert_eq!(err("days = [1]"), "days: expected [profile.NAME] tables");
+        assert_eq!(
+            err("[profile.a]\n  days = [1, \"x\"]"),
+            "profile.a.days: expected an integer day"
+        );
+        assert_eq!(
+            err("[profile.a]\nformat = \"csv\""),
+            "profile.a.format: expected \"table\" or \"json\""
+        );
+        assert_eq!(
+            err("[profile.a]\nspeed = 1"),
+            "profile.a.speed: unknown key"
+        );
+        assert_eq!(err("[profile.a]\n[profile.a]"), "Some((1, 0))");
+        assert_eq!(err("[tool]"), "tool: expected [profile.NAME] tables");
+        assert_eq!(
+            err("[profile.a]\niterations = 4294967296"),
+            "profile.a.iterations: expected at most 4294967295"
+        );
+        assert_eq!(err("[profile.a]\ndays = [1, x]"), "Some((1, 11))");
+    }
+
+    #[test]
+    fn test_run() {
+        let dir = std::env::temp_dir().join(format!("aoc-profile-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("01.txt"),
+            "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n",
+        )
+        .unwrap();
+
+        let mut profile = Profile::new("test");
+        profile.days = vec![1, 2];
+        profile.input = dir.join("{day}.txt").to_string_lossy().into_owned();
+        profile.format = Format::Json;
+        profile.iterations = 2;
+
+        let mut out = Vec::new();
+        assert!(run(&profile, &mut out).unwrap());
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("{\"day\":1,\"part\":1,\"answer\":\"7\","));
+        assert!(lines[1].starts_with("{\"day\":1,\"part\":2,\"answer\":\"5\","));
+        assert!(lines[1].ends_with("\"iterations\":2,\"within_limit\":true}"));
+        assert!(lines[2].starts_with("{\"day\":2,\"error\":\"No input at "));
+
+        profile.format = Format::Table;
+        profile.time_limit = Some(Duration::from_nanos(1));
+        let mut out = Vec::new();
+        assert!(!run(&profile, &mut out).unwrap());
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("Day 1 part 2 took"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_multiline() {
+        let dir = std::env::temp_dir().join(format!("aoc-profile-13-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("13.txt"),
+            "6,10\n0,14\n9,10\n0,3\n10,4\n4,11\n6,0\n6,12\n4,1\n0,13\n10,12\n3,4\n\
+             3,0\n8,4\n1,10\n2,14\n8,10\n9,0\n\nfold along y=7\nfold along x=5\n",
+        )
+        .unwrap();
+
+        let mut profile = Profile::new("test");
+        profile.days = vec![13];
+        profile.input = dir.join("{day}.txt").to_string_lossy().into_owned();
+        profile.output = dir.join("{day}.out").to_string_lossy().into_owned();
+
+        let mut out = Vec::new();
+        assert!(run(&profile, &mut out).unwrap());
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[3].contains("Part 1:"));
+        assert!(lines[4].contains(&format!(
+            "Part 2: {:>50}",
+            format!("See {}", profile.output_path(13))
+        )));
+        assert!(lines.iter().all(|line| !line.contains('#')));
+        assert_eq!(
+            std::fs::read_to_string(profile.output_path(13)).unwrap(),
+            "#####\n#   #\n#   #\n#   #\n#####\n     \n     \n"
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
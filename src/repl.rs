@@ -0,0 +1,315 @@
+//! Interactive stepping through the simulations of days 11, 13 and 14.
+
+use crate::{day09, day11, day13, day14};
+use ndarray::Array2;
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "\
+Commands:
+  load <day> [file]  load day 11, 13 or 14 (default file: inputs/<day>.txt)
+  step [n]           day 11: advance the octopus grid n steps
+  fold [n]           day 13: perform the next n fold instructions
+  insert [n]         day 14: perform n pair insertion steps
+  show               print the current state
+  help               print this message
+  quit               leave the REPL";
+
+/// Paper wider than this is summarised instead of drawn.
+const MAX_PAPER_WIDTH: usize = 120;
+
+enum State {
+    Empty,
+    Octopuses {
+        grid: Array2<Option<usize>>,
+        steps: usize,
+        flashes: usize,
+    },
+    Paper {
+        paper: Array2<usize>,
+        folds: Vec<day13::Coord>,
+        next: usize,
+    },
+    Polymer {
+        polymer: day14::Polymer,
+        rules: day14::Rules,
+        elements: day14::Elements,
+        steps: usize,
+    },
+}
+
+impl State {
+    fn load(day: u8, lines: &[String]) -> Result<Self, String> {
+        match day {
+            11 => Ok(State::Octopuses {
+                grid: day09::parse_input(lines).map_err(|e| e.to_string())?,
+                steps: 0,
+                flashes: 0,
+            }),
+            13 => {
+                let (coords, folds) = day13::parse_input(lines).map_err(|e| e.to_string())?;
+                Ok(State::Paper {
+                    paper: day13::array_from_coords(&coords),
+                    folds,
+                    next: 0,
+                })
+            }
+            14 => {
+                let (polymer, rules, elements) =
+                    day14::parse_lines2(lines).map_err(|e| e.to_string())?;
+                Ok(State::Polymer {
+                    polymer,
+                    rules,
+                    elements,
+                    steps: 0,
+                })
+            }
+            _ => Err(format!("day {} has no steppable state", day)),
+        }
+    }
+
+    fn show(&self) -> String {
+        match self {
+            State::Empty => "Nothing loaded; try `load 11`".to_string(),
+            State::Octopuses {
+                grid,
+                steps,
+                flashes,
+            } => {
+                let mut out = format!("After {} steps, {} flashes:\n", steps, flashes);
+                for row in grid.rows() {
+                    for cell in row.iter().flatten() {
+                        out += &cell.to_string();
+                    }
+                    if row.iter().any(|c| c.is_some()) {
+                        out += "\n";
+                    }
+                }
+                out.trim_end().to_string()
+            }
+            State::Paper { paper, folds, next } => {
+                let summary = format!(
+                    "After {} of {} folds, {} dots visible ({}x{})",
+                    next,
+                    folds.len(),
+                    paper.sum(),
+                    paper.ncols(),
+                    paper.nrows(),
+                );
+                if paper.ncols() > MAX_PAPER_WIDTH {
+                    summary
+                } else {
+                    format!("{}:\n{}", summary, day13::render(paper).trim_end())
+                }
+            }
+            State::Polymer {
+                polymer,
+                elements,
+                steps,
+                ..
+            } => {
+                let mut counts: Vec<_> = elements.iter().collect();
+                counts.sort_unstable();
+                let mut pairs: Vec<_> = polymer.iter().filter(|(_, &n)| n > 0).collect();
+                pairs.sort_unstable();
+                format!(
+                    "After {} steps, length {}:\nelements: {}\npairs: {}",
+                    steps,
+                    elements.values().sum::<usize>(),
+                    counts
+                        .iter()
+                        .map(|(c, n)| format!("{}={}", c, n))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    pairs
+                        .iter()
+                        .map(|(p, n)| format!("{}={}", p, n))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                )
+            }
+        }
+    }
+
+    fn advance(&mut self, command: &str, n: usize) -> Result<(), String> {
+        match (command, self) {
+            (
+                "step",
+                State::Octopuses {
+                    grid,
+                    steps,
+                    flashes,
+                },
+            ) => {
+                for _ in 0..n {
+                    *flashes += day11::step(&mut grid.view_mut());
+                    *steps += 1;
+                }
+                Ok(())
+            }
+            ("fold", State::Paper { paper, folds, next }) => {
+                for _ in 0..n {
+                    let fold = folds.get(*next).ok_or("no folds left")?;
+                    let (axis, index, size) = if fold.x == 0 {
+                        ('y', fold.y, paper.nrows())
+                    } else {
+                        ('x', fold.x, paper.ncols())
+                    };
+                    // `perform_fold` can only fold the far side onto a part at least as big
+                    if index >= size || index < size - 1 - index {
+                        return Err(format!(
+                            "cannot fold along {}={} on paper {}x{}",
+                            axis,
+                            index,
+                            paper.ncols(),
+                            paper.nrows()
+                        ));
+                    }
+                    day13::perform_fold(paper, fold);
+                    *next += 1;
+                }
+                Ok(())
+            }
+            (
+                "insert",
+                State::Polymer {
+                    polymer,
+                    rules,
+                    elements,
+                    steps,
+                },
+            ) => {
+                for _ in 0..n {
+                    day14::step(polymer, rules, elements);
+                    *steps += 1;
+                }
+                Ok(())
+            }
+            (command, _) => Err(format!("`{}` does not apply to the loaded day", command)),
+        }
+    }
+}
+
+fn execute(state: &mut State, line: &str) -> Result<Option<String>, String> {
+    let mut words = line.split_whitespace();
+    let command = match words.next() {
+        Some(command) => command,
+        None => return Ok(None),
+    };
+
+    match command {
+        "load" => {
+            let day: u8 = words
+                .next()
+                .and_then(|d| d.parse().ok())
+                .ok_or("usage: load <day> [file]")?;
+            let path = words
+                .next()
+                .map_or_else(|| format!("inputs/{:02}.txt", day), String::from);
+            let input = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+            let lines = crate::split_lines(&input);
+            let loaded = std::panic::catch_unwind(|| State::load(day, &lines))
+                .map_err(|_| format!("{}: failed to parse input", path))??;
+            *state = loaded;
+            Ok(Some(state.show()))
+        }
+        "step" | "fold" | "insert" => {
+            let n = match words.next() {
+                Some(n) => n.parse().map_err(|_| format!("invalid count {:?}", n))?,
+                None => 1,
+            };
+            state.advance(command, n)?;
+            Ok(Some(state.show()))
+        }
+        "show" => Ok(Some(state.show())),
+        "help" => Ok(Some(HELP.to_string())),
+        _ => Err(format!("unknown command `{}`; try `help`", command)),
+    }
+}
+
+/// Reads commands from `input` until EOF or `quit`, writing results to `output`.
+pub fn repl(input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let mut state = State::Empty;
+
+    write!(output, "> ")?;
+    output.flush()?;
+    for line in input.lines() {
+        let line = line?;
+        if matches!(line.trim(), "quit" | "exit") {
+            return Ok(());
+        }
+        match execute(&mut state, &line) {
+            Ok(Some(result)) => writeln!(output, "{}", result)?,
+            Ok(None) => {}
+            Err(e) => writeln!(output, "error: {}", e)?,
+        }
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+    writeln!(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(input: &[&str]) -> Vec<String> {
+        input.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_step_octopuses() {
+        let mut state =
+            State::load(11, &lines(&["11111", "19991", "19191", "19991", "11111"])).unwrap();
+        state.advance("step", 1).unwrap();
+        assert_eq!(
+            state.show(),
+            "After 1 steps, 9 flashes:\n34543\n40004\n50005\n40004\n34543"
+        );
+        state.advance("step", 1).unwrap();
+        assert!(state.show().ends_with("45654\n51115\n61116\n51115\n45654"));
+        assert!(state.advance("fold", 1).is_err());
+    }
+
+    #[test]
+    fn test_fold_paper() {
+        let mut state = State::load(
+            13,
+            &lines(&["0,0", "4,2", "", "fold along x=2", "fold along y=1"]),
+        )
+        .unwrap();
+        state.advance("fold", 1).unwrap();
+        assert_eq!(
+            state.show(),
+            "After 1 of 2 folds, 2 dots visible (2x3):\n# \n  \n#"
+        );
+        state.advance("fold", 1).unwrap();
+        assert!(state.advance("fold", 1).is_err());
+
+        let mut state = State::load(
+            13,
+            &lines(&["0,0", "4,4", "", "fold along x=500", "fold along y=1"]),
+        )
+        .unwrap();
+        assert_eq!(
+            state.advance("fold", 1),
+            Err("cannot fold along x=500 on paper 5x5".to_string())
+        );
+        assert!(state.show().starts_with("After 0 of 2 folds"));
+    }
+
+    #[test]
+    fn test_repl_session() {
+        let mut state = State::Empty;
+        assert!(execute(&mut state, "step").is_err());
+        assert!(execute(&mut state, "load 1").is_err());
+        assert_eq!(execute(&mut state, "  "), Ok(None));
+
+        let input = "help\nbogus\nquit\nshow\n";
+        let mut output = Vec::new();
+        repl(input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Commands:"));
+        assert!(output.contains("error: unknown command `bogus`"));
+        assert!(!output.contains("Nothing loaded"));
+    }
+}
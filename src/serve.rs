@@ -0,0 +1,273 @@
+//! A minimal HTTP/1.1 server answering `POST /day/{n}/part/{p}` with the
+//! puzzle input as the request body.
+
+use crate::{MyDuration, SolveError};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:2021";
+/// Request bodies larger than this are refused; puzzle inputs are far smaller.
+const MAX_BODY: usize = 1 << 20;
+/// The most bytes read for the request line and headers together.
+const MAX_HEAD: usize = 8 << 10;
+/// How long a client may leave the connection idle before it is dropped.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: format!("{{\"error\":{}}}", json_string(message)),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            431 => "Request Header Fields Too Large",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            c if (c as u32) < 0x20 => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out + "\""
+}
+
+fn parse_route(path: &str) -> Option<(u8, u8)> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match segments.as_slice() {
+        ["day", day, "part", part] => Some((day.parse().ok()?, part.parse().ok()?)),
+        _ => None,
+    }
+}
+
+fn handle(method: &str, path: &str, body: &[u8]) -> Response {
+    let (day, part) = match parse_route(path) {
+        Some(route) => route,
+        None => return Response::error(404, "expected /day/{n}/part/{p}"),
+    };
+
+    if method != "POST" {
+        return Response::error(405, "expected POST");
+    }
+
+    let input = match std::str::from_utf8(body) {
+        Ok(input) => input,
+        Err(_) => return Response::error(400, "input is not valid UTF-8"),
+    };
+
+    let start = Instant::now();
+    let answer = crate::solve_input(day, part, input);
+    let elapsed = start.elapsed();
+
+    match answer {
+        Ok(answer) => Response {
+            status: 200,
+            body: format!(
+                "{{\"day\":{},\"part\":{},\"answer\":{},\"time_ns\":{},\"time\":{}}}",
+                day,
+                part,
+                json_string(&answer),
+                elapsed.as_nanos(),
+                json_string(MyDuration(elapsed).to_string().trim())
+            ),
+        },
+        Err(SolveError::UnknownPuzzle) => Response::error(404, "no such day or part"),
+        Err(SolveError::InvalidInput(e)) => Response::error(422, &e.to_string()),
+        Err(SolveError::SolverFailed) => Response::error(422, "solver failed on this input"),
+    }
+}
+
+/// Reads the request line and the headers up to the blank line, or returns
+/// `None` if they are longer than `MAX_HEAD`.
+fn read_head(reader: &mut impl BufRead) -> io::Result<Option<Vec<String>>> {
+    let mut limited = reader.take(MAX_HEAD as u64);
+    let mut lines = Vec::new();
+    loop {
+        if limited.limit() == 0 {
+            return Ok(None);
+        }
+        let mut line = String::new();
+        if limited.read_line(&mut line)? == 0 {
+            return Ok(Some(lines));
+        }
+        if line.trim().is_empty() && !lines.is_empty() {
+            return Ok(Some(lines));
+        }
+        lines.push(line);
+    }
+}
+
+fn respond(reader: &mut impl BufRead) -> io::Result<Response> {
+    let head = match read_head(reader)? {
+        Some(head) => head,
+        None => {
+            let message = format!("headers are larger than {} bytes", MAX_HEAD);
+            return Ok(Response::error(431, &message));
+        }
+    };
+    let mut request = head.first().map_or("", String::as_str).split_whitespace();
+    let method = request.next().unwrap_or_default();
+    let path = request.next().unwrap_or_default();
+
+    let mut content_length = 0;
+    for header in head.iter().skip(1) {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = match value.trim().parse() {
+                    Ok(length) => length,
+                    Err(_) => return Ok(Response::error(400, "invalid Content-Length")),
+                };
+            }
+        }
+    }
+
+    if content_length > MAX_BODY {
+        let message = format!("input is larger than {} bytes", MAX_BODY);
+        return Ok(Response::error(413, &message));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(handle(method, path, &body))
+}
+
+fn handle_connection(stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let response = respond(&mut reader)?;
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+/// Accepts connections on `listener` forever, one thread per connection.
+pub fn serve_on(listener: TcpListener) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        thread::spawn(move || {
+            if let Err(e) = handle_connection(stream) {
+                eprintln!("serve: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+pub fn serve(addr: impl ToSocketAddrs) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    println!("Listening on http://{}", listener.local_addr()?);
+    serve_on(listener)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, path: &str, body: &str) -> (u16, String) {
+        raw_request(&format!(
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        ))
+    }
+
+    fn raw_request(request: &str) -> (u16, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            handle_connection(stream).unwrap();
+        });
+
+        let mut client = TcpStream::connect(addr).unwrap();
+        client.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        server.join().unwrap();
+
+        let status = response[9..12].parse().unwrap();
+        let body = response.split("\r\n\r\n").nth(1).unwrap().to_string();
+        (status, body)
+    }
+
+    #[test]
+    fn test_solve() {
+        let (status, body) = request("POST", "/day/7/part/2", "16,1,2,0,4,2,7,1,2,14\n");
+        assert_eq!(status, 200);
+        assert!(body.starts_with("{\"day\":7,\"part\":2,\"answer\":\"168\",\"time_ns\":"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(request("POST", "/day/99/part/1", "").0, 404);
+        assert_eq!(request("POST", "/days", "").0, 404);
+        assert_eq!(request("GET", "/day/7/part/1", "").0, 405);
+        assert_eq!(
+            request("POST", "/day/7/part/1", "1,x"),
+            (
+                422,
+                "{\"error\":\"line 1, column 3: invalid number \\\"x\\\"\"}".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_too_large() {
+        let (status, _) =
+            raw_request("POST /day/1/part/1 HTTP/1.1\r\nContent-Length: 99999999999999\r\n\r\n");
+        assert_eq!(status, 413);
+
+        let (status, _) =
+            raw_request("POST /day/1/part/1 HTTP/1.1\r\nContent-Length: 12abc\r\n\r\n");
+        assert_eq!(status, 400);
+
+        let head = format!(
+            "POST /day/1/part/1 HTTP/1.1\r\nX-Padding: {}",
+            "a".repeat(MAX_HEAD)
+        );
+        let (status, body) = raw_request(&head[..MAX_HEAD]);
+        assert_eq!(status, 431);
+        assert_eq!(body, "{\"error\":\"headers are larger than 8192 bytes\"}");
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("a\"b\\c\nd"), "\"a\\\"b\\\\c\\nd\"");
+    }
+}
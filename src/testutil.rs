@@ -0,0 +1,31 @@
+//! Deterministic pseudo-random inputs for the tests and benchmarks.
+
+/// A 64-bit linear congruential generator, so that generated inputs are the
+/// same on every run.
+pub struct Random(pub u64);
+
+impl Random {
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0
+    }
+
+    /// A number in `0..range`.
+    pub fn below(&mut self, range: usize) -> usize {
+        (self.next_u64() >> 33) as usize % range
+    }
+
+    /// The first `count` numbers of a shuffle of `0..n`.
+    pub fn sample(&mut self, n: usize, count: usize) -> Vec<usize> {
+        let mut numbers: Vec<usize> = (0..n).collect();
+        for i in 0..count {
+            let j = i + self.below(n - i);
+            numbers.swap(i, j);
+        }
+        numbers.truncate(count);
+        numbers
+    }
+}
@@ -0,0 +1,79 @@
+//! Opt-in structured events from solver internals, printed to stderr.
+//!
+//! Nothing is printed unless the verbosity has been raised with
+//! [`set_verbosity`], which the binary does for `-v` and `-vv`.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Per-part summaries, enabled by `-v`.
+    Info = 1,
+    /// Per-step detail, enabled by `-vv`.
+    Debug = 2,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_verbosity(verbosity: u8) {
+    VERBOSITY.store(verbosity, Ordering::Relaxed);
+}
+
+pub fn enabled(level: Level) -> bool {
+    VERBOSITY.load(Ordering::Relaxed) >= level as u8
+}
+
+/// Formats an event as `[module] name key=value ...`.
+pub fn format_event(module: &str, name: &str, fields: &[(&str, String)]) -> String {
+    let module = module.rsplit("::").next().unwrap_or(module);
+    let mut line = format!("[{}] {}", module, name);
+    for (key, value) in fields {
+        line += &format!(" {}={}", key, value);
+    }
+    line
+}
+
+/// Emits a structured event if `level` is enabled:
+///
+/// `event!(Level::Debug, "deduced", digit = 9, segments = d9);`
+///
+/// Field values are formatted with `Debug`.
+#[macro_export]
+macro_rules! event {
+    ($level:expr, $name:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        if $crate::trace::enabled($level) {
+            eprintln!(
+                "{}",
+                $crate::trace::format_event(
+                    module_path!(),
+                    $name,
+                    &[$((stringify!($key), format!("{:?}", $value))),*],
+                )
+            );
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_event() {
+        assert_eq!(
+            format_event(
+                "aoc_2021::day13",
+                "fold",
+                &[("axis", "\"x\"".to_string()), ("index", "5".to_string())]
+            ),
+            "[day13] fold axis=\"x\" index=5"
+        );
+        assert_eq!(format_event("day15", "done", &[]), "[day15] done");
+    }
+
+    #[test]
+    fn test_levels() {
+        assert!(Level::Info < Level::Debug);
+        assert!(!enabled(Level::Info));
+    }
+}